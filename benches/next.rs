@@ -0,0 +1,111 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use timer_util::*;
+
+fn datetime(year: i32, month: u32, day: u32, hour: u32, minute: u32, second: u32) -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(year, month, day)
+        .unwrap()
+        .and_hms_opt(hour, minute, second)
+        .unwrap()
+}
+
+/// 每年第366天（只有闰年的12月31日）12:30:15：从不同的起点查找，下个时间点分别在1秒、1天、1个月、1年、4年之后
+fn leap_day() -> TimerConf {
+    configure_yearday(YearDays::default_value(366).unwrap())
+        .build_with_hours(Hours::default_value(H12))
+        .build_with_minuter(Minuters::default_value(M30))
+        .build_with_second(Seconds::default_value(S15))
+}
+
+/// 耗时与下个时间点有多远无关：预先算好每种月份形状选中的日期，以及400年循环内每月是否有选中的日期，
+/// 整月没有选中的日期时直接跳到下个有选中日期的月份，不逐月查看。
+/// CompiledTimer编译时就已算好，查找只是查表和位运算；
+/// TimerConf到明年年底（往前到去年年初）为止逐月现算，更远时交给第一次用到时编译、之后复用的CompiledTimer
+fn bench_distance(c: &mut Criterion) {
+    let conf = leap_day();
+    let compiled = conf.compile();
+    let starts = [
+        ("1s", datetime(2024, 12, 31, 12, 30, 14)),
+        ("1d", datetime(2024, 12, 30, 12, 30, 15)),
+        ("1m", datetime(2024, 11, 30, 12, 30, 15)),
+        ("1y", datetime(2023, 12, 31, 12, 30, 15)),
+        ("4y", datetime(2024, 12, 31, 12, 30, 15)),
+    ];
+    let mut group = c.benchmark_group("next_with_time");
+    for (distance, start) in starts {
+        group.bench_with_input(BenchmarkId::from_parameter(distance), &start, |b, start| {
+            b.iter(|| conf.next_with_time(black_box(*start)))
+        });
+    }
+    group.finish();
+    let mut group = c.benchmark_group("compiled/next_with_time");
+    for (distance, start) in starts {
+        group.bench_with_input(BenchmarkId::from_parameter(distance), &start, |b, start| {
+            b.iter(|| compiled.next_with_time(black_box(*start)))
+        });
+    }
+    group.finish();
+
+    let starts = [
+        ("1s", datetime(2024, 12, 31, 12, 30, 16)),
+        ("1d", datetime(2025, 1, 1, 12, 30, 15)),
+        ("1m", datetime(2025, 1, 31, 12, 30, 15)),
+        ("1y", datetime(2025, 12, 31, 12, 30, 15)),
+        ("4y", datetime(2028, 12, 31, 12, 30, 15)),
+    ];
+    let mut group = c.benchmark_group("prev_with_time");
+    for (distance, start) in starts {
+        group.bench_with_input(BenchmarkId::from_parameter(distance), &start, |b, start| {
+            b.iter(|| conf.prev_with_time(black_box(*start)))
+        });
+    }
+    group.finish();
+    let mut group = c.benchmark_group("compiled/prev_with_time");
+    for (distance, start) in starts {
+        group.bench_with_input(BenchmarkId::from_parameter(distance), &start, |b, start| {
+            b.iter(|| compiled.prev_with_time(black_box(*start)))
+        });
+    }
+    group.finish();
+}
+
+/// 常见的配置：工作日9-18点每15分钟
+fn bench_common(c: &mut Criterion) {
+    let conf = configure_weekday(WeekDays::default_range(W1..=W5).unwrap())
+        .build_with_hours(Hours::default_range(H9..=H18).unwrap())
+        .build_with_minuter(Minuters::every(15).unwrap())
+        .build_with_second(Seconds::default_value(S0));
+    let start = datetime(2024, 3, 8, 18, 45, 0);
+    c.bench_function("next_with_time/workday", |b| {
+        b.iter(|| conf.next_with_time(black_box(start)))
+    });
+    c.bench_function("prev_with_time/workday", |b| {
+        b.iter(|| conf.prev_with_time(black_box(start)))
+    });
+    let compiled = conf.compile();
+    c.bench_function("compiled/next_with_time/workday", |b| {
+        b.iter(|| compiled.next_with_time(black_box(start)))
+    });
+    c.bench_function("compiled/prev_with_time/workday", |b| {
+        b.iter(|| compiled.prev_with_time(black_box(start)))
+    });
+}
+
+/// 永远不会触发的配置（1-7号里的第5周）：查完400年后返回None
+fn bench_never(c: &mut Criterion) {
+    let conf = configure_monthday(MonthDays::default_range(D1..=D7).unwrap())
+        .conf_month_weeks(MonthWeeks::default_value(MonthWeek::Fifth))
+        .build_with_hours(Hours::default_value(H0))
+        .build_with_minuter(Minuters::default_value(M0))
+        .build_with_second(Seconds::default_value(S0));
+    let start = datetime(2024, 1, 1, 0, 0, 0);
+    c.bench_function("next_with_time/never", |b| {
+        b.iter(|| Schedule::next_with_time(&conf, black_box(start)))
+    });
+    c.bench_function("prev_with_time/never", |b| {
+        b.iter(|| Schedule::prev_with_time(&conf, black_box(start)))
+    });
+}
+
+criterion_group!(benches, bench_distance, bench_common, bench_never);
+criterion_main!(benches);
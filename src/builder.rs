@@ -1,27 +1,125 @@
-use crate::conf::{Days, Hours, Minuters, MonthDays, Seconds, TimerConf, WeekDays};
+use crate::calendar::{HolidayCalendar, HolidayPolicy};
+use crate::conf::{
+    Days, DaysMode, Hours, IsoWeeks, Minuters, MonthDays, MonthWeeks, Seconds, SolarTerms,
+    TimerConf, WeekDays, YearDays,
+};
+use crate::data::WeekStart;
+use crate::exclusion::Exclusion;
+use crate::lunar::LunarDays;
+use chrono::NaiveTime;
+use std::sync::Arc;
 
 pub struct DayConfBuilder {
     pub(crate) days: Days,
+    mode: DaysMode,
+    week_start: WeekStart,
 }
 impl DayConfBuilder {
-    pub(crate) fn default_month_days(month_days: MonthDays) -> DayConfBuilder {
+    fn new(days: Days) -> DayConfBuilder {
         DayConfBuilder {
-            days: Days::MonthDays(month_days),
+            days,
+            mode: DaysMode::default(),
+            week_start: WeekStart::default(),
         }
     }
+    pub(crate) fn default_month_days(month_days: MonthDays) -> DayConfBuilder {
+        DayConfBuilder::new(Days::MonthDays(month_days))
+    }
     pub(crate) fn default_week_days(week_days: WeekDays) -> DayConfBuilder {
+        DayConfBuilder::new(Days::WeekDays(week_days))
+    }
+    pub(crate) fn default_work_days(calendar: Arc<dyn HolidayCalendar>) -> DayConfBuilder {
+        DayConfBuilder::new(Days::WorkDays(calendar))
+    }
+    pub fn conf_month_days(self, month_days: MonthDays) -> Self {
         DayConfBuilder {
-            days: Days::WeekDays(week_days),
+            days: self.days.update_month_days(month_days).update_mode(self.mode),
+            mode: self.mode,
+            week_start: self.week_start,
         }
     }
-    pub fn conf_month_days(self, month_days: MonthDays) -> Self {
+    /// 设置月日期与星期的组合方式，默认为DaysMode::Or
+    pub fn conf_mode(self, mode: DaysMode) -> Self {
         DayConfBuilder {
-            days: self.days.update_month_days(month_days),
+            days: self.days.update_mode(mode),
+            mode,
+            week_start: self.week_start,
         }
     }
     pub fn conf_week_days(self, week_days: WeekDays) -> Self {
         DayConfBuilder {
-            days: self.days.update_week_days(week_days),
+            days: self.days.update_week_days(week_days).update_mode(self.mode),
+            mode: self.mode,
+            week_start: self.week_start,
+        }
+    }
+    /// 只保留落在月内第几周的日期，如conf_week_days(W1)后再选中第1、3周，即“每月第1、3个周一”
+    pub fn conf_month_weeks(self, weeks: MonthWeeks) -> Self {
+        DayConfBuilder {
+            days: Days::MonthWeeks {
+                days: Box::new(self.days),
+                weeks,
+                start: None,
+            },
+            mode: self.mode,
+            week_start: self.week_start,
+        }
+    }
+    /// 按日历的行只保留月内第几周的日期：从每周的第一天（conf_week_start）开始，包含1号的那周为第1周，
+    /// 包含最后一天的那周为最后一周。如周一开始时，2024年5月1日（周三）到5日为第1周
+    pub fn conf_calendar_weeks(self, weeks: MonthWeeks) -> Self {
+        DayConfBuilder {
+            days: Days::MonthWeeks {
+                days: Box::new(self.days),
+                weeks,
+                start: Some(self.week_start),
+            },
+            mode: self.mode,
+            week_start: self.week_start,
+        }
+    }
+    /// 设置每周的第一天，影响周数的计算（conf_iso_weeks）及日历的行（conf_calendar_weeks），默认为周一
+    pub fn conf_week_start(self, week_start: WeekStart) -> Self {
+        DayConfBuilder {
+            days: self.days.update_week_start(week_start),
+            mode: self.mode,
+            week_start,
+        }
+    }
+    /// 只保留ISO周数被选中的日期，如“单周的周五”
+    pub fn conf_iso_weeks(self, weeks: IsoWeeks) -> Self {
+        DayConfBuilder {
+            days: Days::IsoWeeks {
+                days: Box::new(self.days),
+                weeks,
+                start: self.week_start,
+            },
+            mode: self.mode,
+            week_start: self.week_start,
+        }
+    }
+    /// 按节假日日历调整选中的日期：选中的日期不是工作日时，按policy跳过或顺延/提前
+    pub fn conf_holidays(
+        self,
+        calendar: impl HolidayCalendar + 'static,
+        policy: HolidayPolicy,
+    ) -> Self {
+        DayConfBuilder {
+            days: Days::Adjusted {
+                days: Box::new(self.days),
+                calendar: Arc::new(calendar),
+                policy,
+            },
+            mode: self.mode,
+            week_start: self.week_start,
+        }
+    }
+    /// 生成屏蔽规则：选中日期里start..=end的时段
+    pub fn build_exclusion(self, start: NaiveTime, end: NaiveTime) -> Exclusion {
+        Exclusion::Period {
+            days: self.days,
+            start,
+            end,
         }
     }
     pub fn build_with_hours(self, hours: Hours) -> DayHourConfBuilder {
@@ -61,12 +159,7 @@ impl DayHourMinuterConfBuilder {
         // } else if self.days.is_zero() {
         //     bail!("day must be selected")
         // }
-        TimerConf {
-            days: self.days,
-            hours: self.hours,
-            minuters: self.minuters,
-            seconds,
-        }
+        TimerConf::new(self.days, self.hours, self.minuters, seconds)
     }
 }
 
@@ -80,3 +173,18 @@ impl From<WeekDays> for DayConfBuilder {
         DayConfBuilder::default_week_days(builder)
     }
 }
+impl From<LunarDays> for DayConfBuilder {
+    fn from(lunar_days: LunarDays) -> Self {
+        DayConfBuilder::new(Days::LunarDays(lunar_days))
+    }
+}
+impl From<SolarTerms> for DayConfBuilder {
+    fn from(solar_terms: SolarTerms) -> Self {
+        DayConfBuilder::new(Days::SolarTerms(solar_terms))
+    }
+}
+impl From<YearDays> for DayConfBuilder {
+    fn from(year_days: YearDays) -> Self {
+        DayConfBuilder::new(Days::YearDays(year_days))
+    }
+}
@@ -0,0 +1,347 @@
+use crate::compute::month_len;
+use crate::conf::{Days, MonthDays};
+use crate::data::MonthDay;
+use crate::error::{Error, Result};
+use crate::traits::{ConfigOperator, FromData};
+use chrono::{Datelike, Duration, NaiveDate};
+use std::collections::BTreeSet;
+use std::fmt::Debug;
+use std::ops::RangeInclusive;
+use std::path::Path;
+use std::sync::Arc;
+
+/// 向前/向后查找工作日的最大天数
+const MAX_SHIFT_DAYS: i64 = 31;
+
+/// 节假日日历
+pub trait HolidayCalendar: Debug + Send + Sync {
+    /// 是否为节假日
+    fn is_holiday(&self, date: NaiveDate) -> bool;
+    /// 是否为工作日：默认周一到周五、且不是节假日
+    fn is_workday(&self, date: NaiveDate) -> bool {
+        date.weekday().number_from_monday() <= 5 && !self.is_holiday(date)
+    }
+    /// 有节假日安排的年份（两端都包含），超出范围的日期只能按周一到周五判断；None表示不限
+    fn covered_years(&self) -> Option<RangeInclusive<i32>> {
+        None
+    }
+}
+
+impl<C: HolidayCalendar + ?Sized> HolidayCalendar for Arc<C> {
+    fn is_holiday(&self, date: NaiveDate) -> bool {
+        (**self).is_holiday(date)
+    }
+    fn is_workday(&self, date: NaiveDate) -> bool {
+        (**self).is_workday(date)
+    }
+    fn covered_years(&self) -> Option<RangeInclusive<i32>> {
+        (**self).covered_years()
+    }
+}
+
+/// 选中的日期不是工作日（周末、节假日）时的处理方式
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum HolidayPolicy {
+    /// 不触发
+    Skip,
+    /// 顺延到下一个工作日
+    NextWorkday,
+    /// 提前到上一个工作日
+    PreviousWorkday,
+}
+
+/// 日期列表形式的节假日日历
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct DateListCalendar(BTreeSet<NaiveDate>);
+
+impl DateListCalendar {
+    pub fn default_array(holidays: &[NaiveDate]) -> Self {
+        Self(holidays.iter().copied().collect())
+    }
+    pub fn add_date(mut self, holiday: NaiveDate) -> Self {
+        self.0.insert(holiday);
+        self
+    }
+    /// 合并另一个日历的节假日
+    pub fn merge(&self, other: &Self) -> Self {
+        Self(self.0.union(&other.0).copied().collect())
+    }
+    /// 解析日期列表：每行一个日期（YYYY-MM-DD），日期后可带说明；空行及#开头的行忽略。如：
+    ///
+    /// ```text
+    /// # 2024
+    /// 2024-01-01 元旦
+    /// 2024-05-01 劳动节
+    /// ```
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut holidays = BTreeSet::new();
+        for (index, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let date = line.split_whitespace().next().unwrap_or_default();
+            let date = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .map_err(|_| Error::parse(index, format!("invalid date `{}`", line)))?;
+            holidays.insert(date);
+        }
+        Ok(Self(holidays))
+    }
+    /// 从文件加载日期列表，格式见[`DateListCalendar::parse`]
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+    /// 解析iCalendar（RFC 5545）：每个VEVENT的DTSTART到DTEND（不包含）之间的日期都视为节假日。
+    /// 不支持RRULE等重复规则
+    pub fn parse_ical(content: &str) -> Result<Self> {
+        let mut holidays = BTreeSet::new();
+        let mut event: Option<(Option<NaiveDate>, Option<NaiveDate>)> = None;
+        let lines = unfold_ical(content);
+        for (index, line) in lines.iter().enumerate() {
+            let (name, value) = line
+                .split_once(':')
+                .ok_or_else(|| Error::parse(index, format!("invalid content line `{}`", line)))?;
+            // 去掉参数，如DTSTART;VALUE=DATE
+            let name = name
+                .split(';')
+                .next()
+                .unwrap_or_default()
+                .to_ascii_uppercase();
+            match (name.as_str(), event.as_mut()) {
+                ("BEGIN", None) if value.eq_ignore_ascii_case("VEVENT") => {
+                    event = Some((None, None))
+                }
+                ("END", Some((start, end))) if value.eq_ignore_ascii_case("VEVENT") => {
+                    let start =
+                        start.ok_or_else(|| Error::parse(index, "VEVENT without DTSTART"))?;
+                    let end = end.unwrap_or_else(|| start.succ());
+                    let mut date = start;
+                    while date < end {
+                        holidays.insert(date);
+                        date = date.succ();
+                    }
+                    event = None;
+                }
+                ("DTSTART", Some((start, _))) => *start = Some(ical_date(value, index)?),
+                ("DTEND", Some((_, end))) => *end = Some(ical_date(value, index)?),
+                _ => {}
+            }
+        }
+        if event.is_some() {
+            return Err(Error::parse(
+                lines.len().saturating_sub(1),
+                "VEVENT is not closed",
+            ));
+        }
+        Ok(Self(holidays))
+    }
+    /// 从文件加载iCalendar，见[`DateListCalendar::parse_ical`]
+    pub fn load_ical(path: impl AsRef<Path>) -> Result<Self> {
+        Self::parse_ical(&std::fs::read_to_string(path)?)
+    }
+}
+
+impl HolidayCalendar for DateListCalendar {
+    fn is_holiday(&self, date: NaiveDate) -> bool {
+        self.0.contains(&date)
+    }
+}
+
+/// 展开iCalendar的折行：以空格或tab开头的行是上一行的延续
+fn unfold_ical(content: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in content.lines() {
+        let line = line.trim_end_matches('\r');
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ if line.trim().is_empty() => {}
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+/// 取iCalendar DATE或DATE-TIME值的日期部分，如20240101、20240101T000000Z
+fn ical_date(value: &str, index: usize) -> Result<NaiveDate> {
+    let date = value.get(..8).unwrap_or(value);
+    NaiveDate::parse_from_str(date, "%Y%m%d")
+        .map_err(|_| Error::parse(index, format!("invalid date `{}`", value)))
+}
+
+/// 给定月份里的所有工作日
+pub(crate) fn workdays(calendar: &dyn HolidayCalendar, year: i32, month: u32) -> MonthDays {
+    let mut month_days = MonthDays::_default();
+    let mut date = NaiveDate::from_ymd(year, month, 1);
+    while date.month() == month {
+        if calendar.is_workday(date) {
+            month_days = month_days.add(MonthDay::from_data(date.day() as u64));
+        }
+        date = date.succ();
+    }
+    month_days
+}
+
+/// 给定月份里，按节假日调整后被选中的日期
+pub(crate) fn adjust(
+    days: &Days,
+    calendar: &dyn HolidayCalendar,
+    policy: HolidayPolicy,
+    year: i32,
+    month: u32,
+) -> MonthDays {
+    let first = NaiveDate::from_ymd(year, month, 1);
+    let last = NaiveDate::from_ymd(year, month, month_len(year, month));
+    let mut selected = MonthCache::new(days);
+    let mut month_days = MonthDays::_default();
+    // 顺延/提前时，相邻月份的日期也可能调整到本月
+    let (mut date, end) = match policy {
+        HolidayPolicy::Skip => (first, last),
+        HolidayPolicy::NextWorkday => (first - Duration::days(MAX_SHIFT_DAYS), last),
+        HolidayPolicy::PreviousWorkday => (first, last + Duration::days(MAX_SHIFT_DAYS)),
+    };
+    while date <= end {
+        if selected.contain(date) {
+            let target = match policy {
+                HolidayPolicy::Skip => Some(date).filter(|x| calendar.is_workday(*x)),
+                HolidayPolicy::NextWorkday => shift(calendar, date, 1),
+                HolidayPolicy::PreviousWorkday => shift(calendar, date, -1),
+            };
+            if let Some(target) = target.filter(|x| first <= *x && *x <= last) {
+                month_days = month_days.add(MonthDay::from_data(target.day() as u64));
+            }
+        }
+        date = date.succ();
+    }
+    month_days
+}
+
+/// 从date开始按step方向查找最近的工作日（包含date）
+fn shift(calendar: &dyn HolidayCalendar, mut date: NaiveDate, step: i64) -> Option<NaiveDate> {
+    for _ in 0..=MAX_SHIFT_DAYS {
+        if calendar.is_workday(date) {
+            return Some(date);
+        }
+        date += Duration::days(step);
+    }
+    None
+}
+
+/// 逐日判断是否选中时，缓存当月的配置
+struct MonthCache<'a> {
+    days: &'a Days,
+    month: Option<(i32, u32)>,
+    month_days: MonthDays,
+}
+
+impl<'a> MonthCache<'a> {
+    fn new(days: &'a Days) -> Self {
+        Self {
+            days,
+            month: None,
+            month_days: MonthDays::_default(),
+        }
+    }
+    fn contain(&mut self, date: NaiveDate) -> bool {
+        let month = (date.year(), date.month());
+        if self.month != Some(month) {
+            self.month_days = self.days.month_days(month.0, month.1);
+            self.month = Some(month);
+        }
+        self.month_days
+            .contain(MonthDay::from_data(date.day() as u64))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{DateListCalendar, HolidayCalendar, HolidayPolicy};
+    use crate::conf::test::datetime;
+    use crate::*;
+    use chrono::NaiveDate;
+
+    fn calendar() -> DateListCalendar {
+        DateListCalendar::parse(
+            "# 2022 国庆\n2022-10-03 国庆节\n2022-10-04\n\n2022-10-05\n2022-10-06\n2022-10-07\n",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parse() -> anyhow::Result<()> {
+        let ical = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nBEGIN:VEVENT\r\nSUMMARY:国庆\r\n \
+                    节\r\nDTSTART;VALUE=DATE:20221003\r\nDTEND;VALUE=DATE:20221008\r\n\
+                    END:VEVENT\r\nEND:VCALENDAR\r\n";
+        assert_eq!(DateListCalendar::parse_ical(ical)?, calendar());
+        assert!(calendar().is_holiday(NaiveDate::from_ymd(2022, 10, 5)));
+        assert!(!calendar().is_workday(NaiveDate::from_ymd(2022, 10, 8)));
+        assert!(calendar().is_workday(NaiveDate::from_ymd(2022, 10, 10)));
+
+        let err = DateListCalendar::parse("2022-10-01\n2022-13-01\n").unwrap_err();
+        assert!(err.to_string().starts_with("line 2"), "{}", err);
+        assert!(DateListCalendar::parse_ical("BEGIN:VEVENT\nDTSTART:20221001\n").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_workday() -> anyhow::Result<()> {
+        let conf = configure_workday(calendar())
+            .build_with_hours(Hours::default_value(H9))
+            .build_with_minuter(Minuters::default_value(M0))
+            .build_with_second(Seconds::default_value(S0));
+        assert_eq!(
+            conf.datetimes(datetime(2022, 9, 30, 0, 0, 0)..datetime(2022, 10, 12, 0, 0, 0))?,
+            vec![
+                datetime(2022, 9, 30, 9, 0, 0),
+                datetime(2022, 10, 10, 9, 0, 0),
+                datetime(2022, 10, 11, 9, 0, 0),
+            ]
+        );
+        assert_eq!(
+            conf.prev_with_time(datetime(2022, 10, 10, 9, 0, 0)),
+            datetime(2022, 9, 30, 9, 0, 0)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_policy() -> anyhow::Result<()> {
+        let build = |policy| {
+            configure_monthday(MonthDays::default_array(&[D1, D5, D20]))
+                .conf_holidays(calendar(), policy)
+                .build_with_hours(Hours::default_value(H9))
+                .build_with_minuter(Minuters::default_value(M0))
+                .build_with_second(Seconds::default_value(S0))
+        };
+        let range = datetime(2022, 9, 25, 0, 0, 0)..datetime(2022, 11, 1, 0, 0, 0);
+        // 10-01是周六，10-05是假期
+        assert_eq!(
+            build(HolidayPolicy::Skip).datetimes(range.clone())?,
+            vec![datetime(2022, 10, 20, 9, 0, 0)]
+        );
+        assert_eq!(
+            build(HolidayPolicy::NextWorkday).datetimes(range.clone())?,
+            vec![
+                datetime(2022, 10, 10, 9, 0, 0),
+                datetime(2022, 10, 20, 9, 0, 0)
+            ]
+        );
+        assert_eq!(
+            build(HolidayPolicy::PreviousWorkday).datetimes(range)?,
+            vec![
+                datetime(2022, 9, 30, 9, 0, 0),
+                datetime(2022, 10, 20, 9, 0, 0),
+            ]
+        );
+        // 顺延可跨月：2022-04-30是周六，顺延到5月
+        let conf = configure_monthday(MonthDays::default_value(D30))
+            .conf_holidays(DateListCalendar::default(), HolidayPolicy::NextWorkday)
+            .build_with_hours(Hours::default_value(H9))
+            .build_with_minuter(Minuters::default_value(M0))
+            .build_with_second(Seconds::default_value(S0));
+        assert_eq!(
+            conf.next_with_time(datetime(2022, 4, 1, 0, 0, 0)),
+            datetime(2022, 5, 2, 9, 0, 0)
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,311 @@
+use crate::calendar::HolidayCalendar;
+use crate::error::{Error, Result};
+use chrono::{Datelike, NaiveDate};
+use std::collections::BTreeSet;
+use std::ops::RangeInclusive;
+use std::path::Path;
+
+/// 内置的国务院办公厅部分节假日安排，格式见[`ChinaCalendar::parse`]
+const BUNDLED: &str = "
+# 2023
+2022-12-31..2023-01-02 休 元旦
+2023-01-21..2023-01-27 休 春节
+2023-01-28 班
+2023-01-29 班
+2023-04-05 休 清明节
+2023-04-23 班
+2023-04-29..2023-05-03 休 劳动节
+2023-05-06 班
+2023-06-22..2023-06-24 休 端午节
+2023-06-25 班
+2023-09-29..2023-10-06 休 中秋节、国庆节
+2023-10-07 班
+2023-10-08 班
+# 2024
+2024-01-01 休 元旦
+2024-02-04 班
+2024-02-10..2024-02-17 休 春节
+2024-02-18 班
+2024-04-04..2024-04-06 休 清明节
+2024-04-07 班
+2024-04-28 班
+2024-05-01..2024-05-05 休 劳动节
+2024-05-11 班
+2024-06-10 休 端午节
+2024-09-14 班
+2024-09-15..2024-09-17 休 中秋节
+2024-09-29 班
+2024-10-01..2024-10-07 休 国庆节
+2024-10-12 班
+# 2025
+2025-01-01 休 元旦
+2025-01-26 班
+2025-01-28..2025-02-04 休 春节
+2025-02-08 班
+2025-04-04..2025-04-06 休 清明节
+2025-04-27 班
+2025-05-01..2025-05-05 休 劳动节
+2025-05-31..2025-06-02 休 端午节
+2025-09-28 班
+2025-10-01..2025-10-08 休 国庆节、中秋节
+2025-10-11 班
+# 2026
+2026-01-01..2026-01-03 休 元旦
+2026-01-04 班
+2026-02-14 班
+2026-02-15..2026-02-23 休 春节
+2026-02-28 班
+2026-04-04..2026-04-06 休 清明节
+2026-05-01..2026-05-05 休 劳动节
+2026-05-09 班
+2026-06-19..2026-06-21 休 端午节
+2026-09-20 班
+2026-09-25..2026-09-27 休 中秋节
+2026-10-01..2026-10-07 休 国庆节
+2026-10-10 班
+";
+
+/// 中国法定节假日日历：按国务院办公厅每年公布的安排，包含放假日期及调休上班的周末
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct ChinaCalendar {
+    holidays: BTreeSet<NaiveDate>,
+    // 调休上班的日期
+    workdays: BTreeSet<NaiveDate>,
+    // 有节假日安排的年份
+    years: BTreeSet<i32>,
+}
+
+impl ChinaCalendar {
+    /// 内置的节假日安排（2023-2026年）
+    pub fn bundled() -> Self {
+        Self::parse(BUNDLED).expect("bundled holidays is invalid")
+    }
+    /// 内置的节假日安排，再用content覆盖
+    pub fn bundled_with(content: &str) -> Result<Self> {
+        Ok(Self::bundled().merge(&Self::parse(content)?))
+    }
+    /// 解析节假日安排：每行一个日期或日期区间（两端都包含），后跟“休”（放假）或“班”（调休上班），
+    /// 之后可带说明；空行及#开头的行忽略。如：
+    ///
+    /// ```text
+    /// # 2024
+    /// 2024-02-04 班
+    /// 2024-02-10..2024-02-17 休 春节
+    /// ```
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut calendar = Self::default();
+        for (index, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut items = line.split_whitespace();
+            let dates = items.next().unwrap_or_default();
+            let (start, end) = match dates.split_once("..") {
+                Some((start, end)) => (parse_date(start, index)?, parse_date(end, index)?),
+                None => {
+                    let date = parse_date(dates, index)?;
+                    (date, date)
+                }
+            };
+            if start > end {
+                return Err(Error::parse(index, format!("{} > {}", start, end)));
+            }
+            let is_holiday = match items.next() {
+                Some("休") => true,
+                Some("班") => false,
+                other => {
+                    return Err(Error::parse(
+                        index,
+                        format!(
+                            "expected `休` or `班`, found `{}`",
+                            other.unwrap_or_default()
+                        ),
+                    ))
+                }
+            };
+            let mut date = start;
+            while date <= end {
+                calendar.set(date, is_holiday);
+                date = date.succ();
+            }
+            // 跨年的区间算作结束日期所在年份的安排，如2023年元旦从2022-12-31开始
+            calendar.years.insert(end.year());
+        }
+        Ok(calendar)
+    }
+    /// 从文件加载节假日安排，格式见[`ChinaCalendar::parse`]
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+    /// 合并另一个日历，同一日期以other为准
+    pub fn merge(&self, other: &Self) -> Self {
+        let mut calendar = self.clone();
+        for date in &other.holidays {
+            calendar.set(*date, true);
+        }
+        for date in &other.workdays {
+            calendar.set(*date, false);
+        }
+        calendar.years.extend(other.years.iter());
+        calendar
+    }
+    /// 包含节假日安排的年份
+    pub fn years(&self) -> BTreeSet<i32> {
+        self.years.clone()
+    }
+    /// 按节假日安排判断是否为工作日；date所在年份没有节假日安排时返回[`Error::OutOfRange`]，
+    /// 而不是像[`HolidayCalendar::is_workday`]那样按周一到周五判断。公元前的年份在错误里记为0
+    pub fn is_workday_checked(&self, date: NaiveDate) -> Result<bool> {
+        if !self.years.contains(&date.year()) {
+            // 按i32查找，只在报告错误时转成u64，负数不会变成很大的值
+            let year = |year: i32| u64::try_from(year).unwrap_or(0);
+            let (min, max) = match self.covered_years() {
+                Some(years) => (year(*years.start()), year(*years.end())),
+                None => (0, 0),
+            };
+            return Err(Error::out_of_range("year", year(date.year()), min, max));
+        }
+        Ok(self.is_workday(date))
+    }
+    /// 是否为调休上班的日期
+    pub fn is_adjusted_workday(&self, date: NaiveDate) -> bool {
+        self.workdays.contains(&date)
+    }
+    fn set(&mut self, date: NaiveDate, is_holiday: bool) {
+        if is_holiday {
+            self.workdays.remove(&date);
+            self.holidays.insert(date);
+        } else {
+            self.holidays.remove(&date);
+            self.workdays.insert(date);
+        }
+    }
+}
+
+fn parse_date(date: &str, index: usize) -> Result<NaiveDate> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map_err(|_| Error::parse(index, format!("invalid date `{}`", date)))
+}
+
+impl HolidayCalendar for ChinaCalendar {
+    fn is_holiday(&self, date: NaiveDate) -> bool {
+        self.holidays.contains(&date)
+    }
+    /// 调休上班的周末也是工作日。没有节假日安排的年份按周一到周五判断，见[`ChinaCalendar::covered_years`]
+    fn is_workday(&self, date: NaiveDate) -> bool {
+        self.workdays.contains(&date)
+            || (date.weekday().number_from_monday() <= 5 && !self.holidays.contains(&date))
+    }
+    /// 最早到最晚有节假日安排的年份，中间缺少的年份见[`ChinaCalendar::years`]
+    fn covered_years(&self) -> Option<RangeInclusive<i32>> {
+        let first = *self.years.iter().next()?;
+        let last = *self.years.iter().next_back()?;
+        Some(first..=last)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ChinaCalendar;
+    use crate::conf::test::datetime;
+    use crate::*;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_china_calendar() -> anyhow::Result<()> {
+        let calendar = ChinaCalendar::bundled();
+        assert_eq!(
+            calendar.years().into_iter().collect::<Vec<_>>(),
+            vec![2023, 2024, 2025, 2026]
+        );
+        assert_eq!(calendar.covered_years(), Some(2023..=2026));
+        // 2024-02-04是周日，调休上班
+        assert!(calendar.is_workday(NaiveDate::from_ymd(2024, 2, 4)));
+        assert!(calendar.is_holiday(NaiveDate::from_ymd(2024, 2, 16)));
+        assert!(!calendar.is_workday(NaiveDate::from_ymd(2024, 2, 16)));
+
+        // 工作日：2024年春节前后
+        let conf = configure_workday(calendar)
+            .build_with_hours(Hours::default_value(H9))
+            .build_with_minuter(Minuters::default_value(M0))
+            .build_with_second(Seconds::default_value(S0));
+        assert_eq!(
+            conf.datetimes(datetime(2024, 2, 3, 0, 0, 0)..datetime(2024, 2, 21, 0, 0, 0))?,
+            vec![
+                datetime(2024, 2, 4, 9, 0, 0),
+                datetime(2024, 2, 5, 9, 0, 0),
+                datetime(2024, 2, 6, 9, 0, 0),
+                datetime(2024, 2, 7, 9, 0, 0),
+                datetime(2024, 2, 8, 9, 0, 0),
+                datetime(2024, 2, 9, 9, 0, 0),
+                datetime(2024, 2, 18, 9, 0, 0),
+                datetime(2024, 2, 19, 9, 0, 0),
+                datetime(2024, 2, 20, 9, 0, 0),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge() -> anyhow::Result<()> {
+        // 用户文件覆盖内置安排：2024-02-09（除夕）放假
+        let calendar = ChinaCalendar::bundled_with("2024-02-09 休 除夕\n2024-02-18 休\n")?;
+        assert!(calendar.is_holiday(NaiveDate::from_ymd(2024, 2, 9)));
+        assert!(!calendar.is_adjusted_workday(NaiveDate::from_ymd(2024, 2, 18)));
+        assert!(!calendar.is_workday(NaiveDate::from_ymd(2024, 2, 18)));
+
+        let err = ChinaCalendar::parse("2024-02-09 上班\n").unwrap_err();
+        assert!(err.to_string().starts_with("line 1"), "{}", err);
+        assert!(ChinaCalendar::parse("2024-02-17..2024-02-10 休\n").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_covered_years() -> anyhow::Result<()> {
+        let calendar = ChinaCalendar::bundled();
+        // 2024-02-04是周日，调休上班
+        assert!(calendar.is_workday_checked(NaiveDate::from_ymd(2024, 2, 4))?);
+        // 没有2030年的安排：is_workday按周一到周五判断，is_workday_checked返回错误
+        let date = NaiveDate::from_ymd(2030, 10, 1);
+        assert!(calendar.is_workday(date));
+        assert!(matches!(
+            calendar.is_workday_checked(date),
+            Err(Error::OutOfRange {
+                value: 2030,
+                min: 2023,
+                max: 2026,
+                ..
+            })
+        ));
+        // 2022-12-31属于2023年的安排，2022年本身没有
+        assert!(calendar
+            .is_workday_checked(NaiveDate::from_ymd(2022, 12, 30))
+            .is_err());
+        // 公元前的年份同样没有安排，不会转成很大的年份
+        assert!(matches!(
+            calendar.is_workday_checked(NaiveDate::from_ymd(-2024, 10, 1)),
+            Err(Error::OutOfRange {
+                value: 0,
+                min: 2023,
+                max: 2026,
+                ..
+            })
+        ));
+        assert!(matches!(
+            calendar.is_workday_checked(NaiveDate::from_ymd(0, 10, 1)),
+            Err(Error::OutOfRange { value: 0, .. })
+        ));
+
+        let calendar = ChinaCalendar::bundled_with("2030-10-01..2030-10-07 休 国庆节\n")?;
+        assert_eq!(calendar.covered_years(), Some(2023..=2030));
+        assert!(!calendar.is_workday_checked(date)?);
+        assert!(calendar
+            .is_workday_checked(NaiveDate::from_ymd(2028, 10, 9))
+            .is_err());
+
+        assert_eq!(ChinaCalendar::default().covered_years(), None);
+        assert_eq!(DateListCalendar::default().covered_years(), None);
+        Ok(())
+    }
+}
@@ -0,0 +1,433 @@
+use crate::schedule::{horizon, Schedule};
+use chrono::{Duration, NaiveDateTime};
+use std::ops::{Add, Sub};
+
+/// 并集：符合任一计划即触发。
+///
+/// 与字段级的[`merge`](crate::ConfigOperator::merge)不同：字段级合并得到的是各字段的笛卡尔积，
+/// 而Union只包含两个计划各自的时间点
+#[derive(Debug, Clone)]
+pub struct Union<A: Schedule, B: Schedule> {
+    a: A,
+    b: B,
+}
+/// 交集：同时符合两个计划才触发
+#[derive(Debug, Clone)]
+pub struct Intersect<A: Schedule, B: Schedule> {
+    a: A,
+    b: B,
+}
+/// 差集：符合计划A、且不符合计划B时才触发
+#[derive(Debug, Clone)]
+pub struct Except<A: Schedule, B: Schedule> {
+    a: A,
+    b: B,
+}
+
+impl<A: Schedule, B: Schedule> Union<A, B> {
+    pub fn new(a: A, b: B) -> Self {
+        Self { a, b }
+    }
+}
+impl<A: Schedule, B: Schedule> Intersect<A, B> {
+    pub fn new(a: A, b: B) -> Self {
+        Self { a, b }
+    }
+}
+impl<A: Schedule, B: Schedule> Except<A, B> {
+    pub fn new(a: A, b: B) -> Self {
+        Self { a, b }
+    }
+}
+
+impl<A: Schedule, B: Schedule> Schedule for Union<A, B> {
+    fn next_with_time(&self, now: NaiveDateTime) -> Option<NaiveDateTime> {
+        earlier(self.a.next_with_time(now), self.b.next_with_time(now))
+    }
+    fn prev_with_time(&self, now: NaiveDateTime) -> Option<NaiveDateTime> {
+        later(self.a.prev_with_time(now), self.b.prev_with_time(now))
+    }
+    fn matches(&self, datetime: NaiveDateTime) -> bool {
+        self.a.matches(datetime) || self.b.matches(datetime)
+    }
+    /// 两者都不符合才不符合：在两者下个不符合的时间点之前一直符合，因此跳到较晚的那个，直到两者一致
+    fn next_unmatched(&self, mut now: NaiveDateTime) -> Option<NaiveDateTime> {
+        let limit = horizon(now, true);
+        loop {
+            let a = self.a.next_unmatched(now)?;
+            let b = self.b.next_unmatched(now)?;
+            if a == b {
+                return Some(a);
+            }
+            let later = a.max(b);
+            if later > limit {
+                return None;
+            }
+            now = later.sub(Duration::seconds(1));
+        }
+    }
+    fn prev_unmatched(&self, mut now: NaiveDateTime) -> Option<NaiveDateTime> {
+        let limit = horizon(now, false);
+        loop {
+            let a = self.a.prev_unmatched(now)?;
+            let b = self.b.prev_unmatched(now)?;
+            if a == b {
+                return Some(a);
+            }
+            let earlier = a.min(b);
+            if earlier < limit {
+                return None;
+            }
+            now = earlier.add(Duration::seconds(1));
+        }
+    }
+    /// 两者有效期的并集，任一端不限制则不限制
+    fn valid_range(&self) -> (Option<NaiveDateTime>, Option<NaiveDateTime>) {
+        let (a_start, a_end) = self.a.valid_range();
+        let (b_start, b_end) = self.b.valid_range();
+        (a_start.min(b_start), a_end.zip(b_end).map(|(a, b)| a.max(b)))
+    }
+}
+
+impl<A: Schedule, B: Schedule> Schedule for Intersect<A, B> {
+    /// 两个计划交替推进：共同的时间点一定不早于两者下个时间点中较晚的那个
+    fn next_with_time(&self, mut now: NaiveDateTime) -> Option<NaiveDateTime> {
+        let limit = horizon(now, true);
+        loop {
+            let a = self.a.next_with_time(now)?;
+            let b = self.b.next_with_time(now)?;
+            if a == b {
+                return Some(a);
+            }
+            let later = a.max(b);
+            if later > limit {
+                return None;
+            }
+            now = later.sub(Duration::seconds(1));
+        }
+    }
+    fn prev_with_time(&self, mut now: NaiveDateTime) -> Option<NaiveDateTime> {
+        let limit = horizon(now, false);
+        loop {
+            let a = self.a.prev_with_time(now)?;
+            let b = self.b.prev_with_time(now)?;
+            if a == b {
+                return Some(a);
+            }
+            let earlier = a.min(b);
+            if earlier < limit {
+                return None;
+            }
+            now = earlier.add(Duration::seconds(1));
+        }
+    }
+    fn matches(&self, datetime: NaiveDateTime) -> bool {
+        self.a.matches(datetime) && self.b.matches(datetime)
+    }
+    /// 任一个不符合即不符合，取两者下个不符合的时间点中较早的那个
+    fn next_unmatched(&self, now: NaiveDateTime) -> Option<NaiveDateTime> {
+        earlier(self.a.next_unmatched(now), self.b.next_unmatched(now))
+    }
+    fn prev_unmatched(&self, now: NaiveDateTime) -> Option<NaiveDateTime> {
+        later(self.a.prev_unmatched(now), self.b.prev_unmatched(now))
+    }
+    /// 两者有效期的交集
+    fn valid_range(&self) -> (Option<NaiveDateTime>, Option<NaiveDateTime>) {
+        let (a_start, a_end) = self.a.valid_range();
+        let (b_start, b_end) = self.b.valid_range();
+        let end = match (a_end, b_end) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        (a_start.max(b_start), end)
+    }
+}
+
+impl<A: Schedule, B: Schedule> Schedule for Except<A, B> {
+    /// A的时间点落在B连续符合的时段内时，直接跳到该时段之后；B一直符合则不会再触发
+    fn next_with_time(&self, mut now: NaiveDateTime) -> Option<NaiveDateTime> {
+        let limit = horizon(now, true);
+        loop {
+            let next = self.a.next_with_time(now)?;
+            if !self.b.matches(next) {
+                return Some(next);
+            }
+            let gap = self.b.next_unmatched(next)?;
+            if gap > limit {
+                return None;
+            }
+            now = gap.sub(Duration::seconds(1));
+        }
+    }
+    fn prev_with_time(&self, mut now: NaiveDateTime) -> Option<NaiveDateTime> {
+        let limit = horizon(now, false);
+        loop {
+            let prev = self.a.prev_with_time(now)?;
+            if !self.b.matches(prev) {
+                return Some(prev);
+            }
+            let gap = self.b.prev_unmatched(prev)?;
+            if gap < limit {
+                return None;
+            }
+            now = gap.add(Duration::seconds(1));
+        }
+    }
+    fn matches(&self, datetime: NaiveDateTime) -> bool {
+        self.a.matches(datetime) && !self.b.matches(datetime)
+    }
+    /// A不符合或B符合即不符合，取两者中较早的那个
+    fn next_unmatched(&self, now: NaiveDateTime) -> Option<NaiveDateTime> {
+        earlier(self.a.next_unmatched(now), self.b.next_with_time(now))
+    }
+    fn prev_unmatched(&self, now: NaiveDateTime) -> Option<NaiveDateTime> {
+        later(self.a.prev_unmatched(now), self.b.prev_with_time(now))
+    }
+    fn valid_range(&self) -> (Option<NaiveDateTime>, Option<NaiveDateTime>) {
+        self.a.valid_range()
+    }
+}
+
+/// 较早的时间点，None表示没有
+pub(crate) fn earlier(a: Option<NaiveDateTime>, b: Option<NaiveDateTime>) -> Option<NaiveDateTime> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// 较晚的时间点，None表示没有
+pub(crate) fn later(a: Option<NaiveDateTime>, b: Option<NaiveDateTime>) -> Option<NaiveDateTime> {
+    a.max(b)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::conf::test::{check_unmatched, datetime};
+    use crate::*;
+    use chrono::Duration;
+
+    fn all_day(days: impl Into<builder::DayConfBuilder>) -> TimerConf {
+        days.into()
+            .build_with_hours(Hours::default_all())
+            .build_with_minuter(Minuters::default_all())
+            .build_with_second(Seconds::default_all())
+    }
+
+    #[test]
+    fn test_combined_unmatched() -> anyhow::Result<()> {
+        let every_minute = configure_monthday(MonthDays::default_all())
+            .build_with_hours(Hours::default_all())
+            .build_with_minuter(Minuters::default_all())
+            .build_with_second(Seconds::default_value(S0));
+        let weekdays = all_day(WeekDays::default_range(W1..=W5)?);
+        let weekends = all_day(WeekDays::default_array(&[W6, W7]));
+        // 并集一直符合：整段跳过，而不是逐秒查找400年
+        let always = weekdays.clone().union(weekends.clone());
+        let now = datetime(2022, 5, 6, 8, 59, 0);
+        assert_eq!(always.next_unmatched(now), None);
+        assert_eq!(always.prev_unmatched(now), None);
+        let except = every_minute.except(always);
+        assert_eq!(except.next_with_time(now), None);
+        assert_eq!(except.prev_with_time(now), None);
+
+        // 与逐秒判断的结果一致。2022-05-06是周五
+        let morning = configure_monthday(MonthDays::default_all())
+            .build_with_hours(Hours::default_range(H8..=H11)?)
+            .build_with_minuter(Minuters::default_all())
+            .build_with_second(Seconds::default_all());
+        let union = weekends.clone().union(morning.clone());
+        let intersect = weekdays.clone().intersect(morning.clone());
+        let except = weekdays.except(morning);
+        for now in [
+            now,
+            datetime(2022, 5, 6, 12, 0, 0),
+            datetime(2022, 5, 7, 9, 0, 0),
+            datetime(2022, 5, 9, 0, 0, 0),
+        ] {
+            check_unmatched(&union, now, 3);
+            check_unmatched(&intersect, now, 3);
+            check_unmatched(&except, now, 3);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_union() -> anyhow::Result<()> {
+        // 每周一10:00 或 每月15号 18:30
+        let monday = configure_weekday(WeekDays::default_value(W1))
+            .build_with_hours(Hours::default_value(H10))
+            .build_with_minuter(Minuters::default_value(M0))
+            .build_with_second(Seconds::default_value(S0));
+        let fifteenth = configure_monthday(MonthDays::default_value(D15))
+            .build_with_hours(Hours::default_value(H18))
+            .build_with_minuter(Minuters::default_value(M30))
+            .build_with_second(Seconds::default_value(S0));
+        let union = monday.union(fifteenth);
+        assert_eq!(
+            union.datetimes(datetime(2022, 8, 1, 0, 0, 0)..datetime(2022, 8, 20, 0, 0, 0))?,
+            vec![
+                datetime(2022, 8, 1, 10, 0, 0),
+                datetime(2022, 8, 8, 10, 0, 0),
+                datetime(2022, 8, 15, 10, 0, 0),
+                datetime(2022, 8, 15, 18, 30, 0),
+            ]
+        );
+        // 字段级合并是笛卡尔积，会多出 周一18:30、15号10:00
+        assert!(!union.matches(datetime(2022, 8, 8, 18, 30, 0)));
+        assert_eq!(
+            union.prev_with_time(datetime(2022, 8, 15, 18, 30, 0)),
+            Some(datetime(2022, 8, 15, 10, 0, 0))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_intersect_except() -> anyhow::Result<()> {
+        // 每周五 与 每月13号 的交集：黑色星期五
+        let friday = configure_weekday(WeekDays::default_value(W5))
+            .build_with_hours(Hours::default_value(H9))
+            .build_with_minuter(Minuters::default_value(M0))
+            .build_with_second(Seconds::default_value(S0));
+        let thirteenth = configure_monthday(MonthDays::default_value(D13))
+            .build_with_hours(Hours::default_all())
+            .build_with_minuter(Minuters::default_value(M0))
+            .build_with_second(Seconds::default_value(S0));
+        let intersect = friday.clone().intersect(thirteenth.clone());
+        assert_eq!(
+            intersect.next_with_time(datetime(2022, 1, 1, 0, 0, 0)),
+            Some(datetime(2022, 5, 13, 9, 0, 0))
+        );
+        assert_eq!(
+            intersect.next_with_time(datetime(2022, 5, 13, 9, 0, 0)),
+            Some(datetime(2023, 1, 13, 9, 0, 0))
+        );
+        assert_eq!(
+            intersect.prev_with_time(datetime(2023, 1, 13, 9, 0, 0)),
+            Some(datetime(2022, 5, 13, 9, 0, 0))
+        );
+
+        let except = friday.except(thirteenth);
+        assert_eq!(
+            except.datetimes(datetime(2022, 5, 1, 0, 0, 0)..datetime(2022, 5, 31, 0, 0, 0))?,
+            vec![
+                datetime(2022, 5, 6, 9, 0, 0),
+                datetime(2022, 5, 20, 9, 0, 0),
+                datetime(2022, 5, 27, 9, 0, 0),
+            ]
+        );
+        assert_eq!(
+            except.prev_with_time(datetime(2022, 5, 20, 9, 0, 0)),
+            Some(datetime(2022, 5, 6, 9, 0, 0))
+        );
+
+        // 没有交集
+        let never = FixedTimes::once(datetime(2022, 5, 13, 9, 0, 1)).intersect(intersect);
+        assert_eq!(never.next_with_time(datetime(2022, 1, 1, 0, 0, 0)), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_except_skip_runs() -> anyhow::Result<()> {
+        let every_second = configure_monthday(MonthDays::default_all())
+            .build_with_hours(Hours::default_all())
+            .build_with_minuter(Minuters::default_all())
+            .build_with_second(Seconds::default_all());
+        // B覆盖了A的所有时间点
+        let never = every_second.clone().except(every_second.clone());
+        assert_eq!(never.next_with_time(datetime(2022, 1, 1, 0, 0, 0)), None);
+        assert_eq!(never.prev_with_time(datetime(2022, 1, 1, 0, 0, 0)), None);
+
+        // 每分钟，工作日9-17点除外
+        let every_minute = configure_monthday(MonthDays::default_all())
+            .build_with_hours(Hours::default_all())
+            .build_with_minuter(Minuters::default_all())
+            .build_with_second(Seconds::default_value(S0));
+        let office = configure_weekday(WeekDays::default_range(W1..=W5)?)
+            .build_with_hours(Hours::default_range(H9..=H17)?)
+            .build_with_minuter(Minuters::default_all())
+            .build_with_second(Seconds::default_all());
+        let off_hours = every_minute.clone().except(office.clone());
+        // 2022-05-06是周五
+        assert_eq!(
+            off_hours.next_with_time(datetime(2022, 5, 6, 8, 59, 0)),
+            Some(datetime(2022, 5, 6, 18, 0, 0))
+        );
+        assert_eq!(
+            off_hours.prev_with_time(datetime(2022, 5, 6, 18, 0, 0)),
+            Some(datetime(2022, 5, 6, 8, 59, 0))
+        );
+        assert_eq!(
+            office.next_unmatched(datetime(2022, 5, 6, 12, 0, 0)),
+            Some(datetime(2022, 5, 6, 18, 0, 0))
+        );
+        assert_eq!(
+            office.prev_unmatched(datetime(2022, 5, 9, 12, 0, 0)),
+            Some(datetime(2022, 5, 9, 8, 59, 59))
+        );
+
+        // 有效期内每秒都符合，之后都不符合
+        let window = every_second
+            .not_before(datetime(2022, 5, 1, 0, 0, 0))
+            .not_after(datetime(2022, 5, 31, 23, 59, 59));
+        let outside = every_minute.except(window);
+        assert_eq!(
+            outside.next_with_time(datetime(2022, 4, 30, 23, 59, 0)),
+            Some(datetime(2022, 6, 1, 0, 0, 0))
+        );
+        assert_eq!(
+            outside.prev_with_time(datetime(2022, 6, 1, 0, 0, 0)),
+            Some(datetime(2022, 4, 30, 23, 59, 0))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_unmatched() -> anyhow::Result<()> {
+        // 逐秒判断的结果与按位查找的结果一致
+        let confs = [
+            configure_monthday(MonthDays::default_array(&[D1, D2, D31]))
+                .build_with_hours(Hours::default_array(&[H0, H23]))
+                .build_with_minuter(Minuters::default_range(M58..)?)
+                .build_with_second(Seconds::default_all()),
+            configure_weekday(WeekDays::default_value(W7))
+                .build_with_hours(Hours::default_all())
+                .build_with_minuter(Minuters::default_all())
+                .build_with_second(Seconds::default_range(S1..)?),
+        ];
+        let starts = [
+            datetime(2022, 1, 1, 0, 57, 59),
+            datetime(2022, 1, 1, 23, 59, 58),
+            datetime(2022, 1, 2, 0, 0, 0),
+            datetime(2022, 3, 31, 23, 58, 30),
+            datetime(2022, 5, 1, 0, 0, 5),
+        ];
+        for conf in confs.iter() {
+            for start in starts {
+                let mut next = start + Duration::seconds(1);
+                while conf.matches(next) {
+                    next += Duration::seconds(1);
+                }
+                assert_eq!(
+                    conf.next_unmatched(start),
+                    Some(next),
+                    "{:?} {}",
+                    conf,
+                    start
+                );
+                let mut prev = start - Duration::seconds(1);
+                while conf.matches(prev) {
+                    prev -= Duration::seconds(1);
+                }
+                assert_eq!(
+                    conf.prev_unmatched(start),
+                    Some(prev),
+                    "{:?} {}",
+                    conf,
+                    start
+                );
+            }
+        }
+        Ok(())
+    }
+}
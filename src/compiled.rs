@@ -0,0 +1,578 @@
+use crate::compute::{add_months, month_len, MonthMasks};
+use crate::conf::{Hours, Minuters, Seconds, TimerConf};
+use crate::schedule::{Schedule, SEARCH_YEARS};
+use crate::traits::{bits_below, bits_from, bits_to, highest, lowest, ConfigOperator};
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+
+/// 预编译的定时器：由[`TimerConf`]生成一次，之后反复查找下个/上个时间点。
+///
+/// 日期只由月份形状决定时（月日期、星期、每年第几天、月内第几周及其组合），预先算好每种形状选中的日期；
+/// 查找时只做位运算，不克隆配置，也不分配内存。
+/// 其余日期配置（工作日、农历、节气、节假日调整、ISO周）第一次用到某年时整年算好记下，之后同一年只查表
+#[derive(Debug, Clone)]
+pub struct CompiledTimer {
+    days: MonthMasks,
+    // 可能有选中日期的年份范围，见Days::years
+    years: (Option<i32>, Option<i32>),
+    hours: u64,
+    minuters: u64,
+    seconds: u64,
+}
+
+impl CompiledTimer {
+    pub fn new(conf: &TimerConf) -> Self {
+        Self {
+            days: MonthMasks::new(conf.days.clone()).fill(),
+            years: conf.days.years(),
+            hours: conf.hours._val() & Hours::_mask(),
+            minuters: conf.minuters._val() & Minuters::_mask(),
+            seconds: conf.seconds._val() & Seconds::_mask(),
+        }
+    }
+
+    /// 与other在同一秒触发的时间点：日期、时、分、秒逐项取交集
+    pub(crate) fn intersection(&self, other: &CompiledTimer) -> CompiledTimer {
+        // 年份范围取两者中更严的限制
+        let (x, y) = (self.years, other.years);
+        Self {
+            days: self.days.intersection(&other.days),
+            years: (x.0.max(y.0), x.1.into_iter().chain(y.1).min()),
+            hours: self.hours & other.hours,
+            minuters: self.minuters & other.minuters,
+            seconds: self.seconds & other.seconds,
+        }
+    }
+
+    /// 给定的时间点是否符合
+    pub fn matches(&self, datetime: NaiveDateTime) -> bool {
+        let date = datetime.date();
+        let time = datetime.time();
+        self.month_mask(date.year(), date.month()) & (1 << date.day()) > 0
+            && self.hours & (1 << time.hour()) > 0
+            && self.minuters & (1 << time.minute()) > 0
+            && self.seconds & (1 << time.second()) > 0
+    }
+
+    /// 是否一定不会触发：时、分、秒有一项没选，或者每种月份形状都没有选中的日期
+    fn is_never(&self) -> bool {
+        self.hours == 0 || self.minuters == 0 || self.seconds == 0 || self.days.is_never()
+    }
+
+    /// 给定月份选中的日期，不超过当月的天数
+    fn month_mask(&self, year: i32, month: u32) -> u64 {
+        self.days.mask(year, month)
+    }
+
+    /// 当天不早于(hour, minuter, second)的第一个符合的时刻
+    fn first_time(&self, hour: u32, minuter: u32, second: u32) -> Option<NaiveTime> {
+        if self.hours & (1 << hour) > 0 {
+            if self.minuters & (1 << minuter) > 0 {
+                if let Some(second) = lowest(self.seconds & bits_from(second)) {
+                    return Some(to_time(hour as u64, minuter as u64, second));
+                }
+            }
+            if let Some(minuter) = lowest(self.minuters & bits_from(minuter + 1)) {
+                return Some(to_time(hour as u64, minuter, lowest(self.seconds)?));
+            }
+        }
+        let hour = lowest(self.hours & bits_from(hour + 1))?;
+        Some(to_time(hour, lowest(self.minuters)?, lowest(self.seconds)?))
+    }
+
+    /// 当天不晚于(hour, minuter, second)的最后一个符合的时刻
+    fn last_time(&self, hour: u32, minuter: u32, second: u32) -> Option<NaiveTime> {
+        if self.hours & (1 << hour) > 0 {
+            if self.minuters & (1 << minuter) > 0 {
+                if let Some(second) = highest(self.seconds & bits_to(second)) {
+                    return Some(to_time(hour as u64, minuter as u64, second));
+                }
+            }
+            if let Some(minuter) = highest(self.minuters & bits_below(minuter)) {
+                return Some(to_time(hour as u64, minuter, highest(self.seconds)?));
+            }
+        }
+        let hour = highest(self.hours & bits_below(hour))?;
+        Some(to_time(
+            hour,
+            highest(self.minuters)?,
+            highest(self.seconds)?,
+        ))
+    }
+    /// 当天不早于(hour, minuter, second)的第一个不符合的时刻
+    fn first_gap(&self, hour: u32, minuter: u32, second: u32) -> Option<NaiveTime> {
+        if self.hours & (1 << hour) == 0 || self.minuters & (1 << minuter) == 0 {
+            return Some(to_time(hour as u64, minuter as u64, second as u64));
+        }
+        if let Some(second) = lowest(!self.seconds & Seconds::_mask() & bits_from(second)) {
+            return Some(to_time(hour as u64, minuter as u64, second));
+        }
+        if let Some(time) = self.gap_in_hour(hour, minuter + 1) {
+            return Some(time);
+        }
+        for hour in hour + 1..24 {
+            if self.hours & (1 << hour) == 0 {
+                return Some(to_time(hour as u64, 0, 0));
+            }
+            if let Some(time) = self.gap_in_hour(hour, 0) {
+                return Some(time);
+            }
+        }
+        None
+    }
+
+    /// 选中的hour内，不早于minuter:00的第一个不符合的时刻
+    fn gap_in_hour(&self, hour: u32, minuter: u32) -> Option<NaiveTime> {
+        if minuter > 59 {
+            return None;
+        }
+        if self.minuters & (1 << minuter) == 0 {
+            return Some(to_time(hour as u64, minuter as u64, 0));
+        }
+        if let Some(second) = lowest(!self.seconds & Seconds::_mask()) {
+            return Some(to_time(hour as u64, minuter as u64, second));
+        }
+        // 秒全选时，只有没选中的分钟不符合
+        lowest(!self.minuters & Minuters::_mask() & bits_from(minuter))
+            .map(|minuter| to_time(hour as u64, minuter, 0))
+    }
+
+    /// 当天不晚于(hour, minuter, second)的最后一个不符合的时刻
+    fn last_gap(&self, hour: u32, minuter: u32, second: u32) -> Option<NaiveTime> {
+        if self.hours & (1 << hour) == 0 || self.minuters & (1 << minuter) == 0 {
+            return Some(to_time(hour as u64, minuter as u64, second as u64));
+        }
+        if let Some(second) = highest(!self.seconds & Seconds::_mask() & bits_to(second)) {
+            return Some(to_time(hour as u64, minuter as u64, second));
+        }
+        if minuter > 0 {
+            if let Some(time) = self.gap_in_hour_back(hour, minuter - 1) {
+                return Some(time);
+            }
+        }
+        for hour in (0..hour).rev() {
+            if self.hours & (1 << hour) == 0 {
+                return Some(to_time(hour as u64, 59, 59));
+            }
+            if let Some(time) = self.gap_in_hour_back(hour, 59) {
+                return Some(time);
+            }
+        }
+        None
+    }
+
+    /// 选中的hour内，不晚于minuter:59的最后一个不符合的时刻
+    fn gap_in_hour_back(&self, hour: u32, minuter: u32) -> Option<NaiveTime> {
+        if self.minuters & (1 << minuter) == 0 {
+            return Some(to_time(hour as u64, minuter as u64, 59));
+        }
+        if let Some(second) = highest(!self.seconds & Seconds::_mask()) {
+            return Some(to_time(hour as u64, minuter as u64, second));
+        }
+        highest(!self.minuters & Minuters::_mask() & bits_to(minuter))
+            .map(|minuter| to_time(hour as u64, minuter, 59))
+    }
+
+    /// 时、分、秒是否全选：此时只有没选中的日期不符合
+    fn is_all_day(&self) -> bool {
+        self.hours == Hours::_mask()
+            && self.minuters == Minuters::_mask()
+            && self.seconds == Seconds::_mask()
+    }
+}
+
+impl Schedule for CompiledTimer {
+    fn next_with_time(&self, now: NaiveDateTime) -> Option<NaiveDateTime> {
+        if self.is_never() {
+            return None;
+        }
+        let start = now.checked_add_signed(Duration::seconds(1))?;
+        let (mut year, mut month) = (start.year(), start.month());
+        // 只有第一天需要考虑当前时刻，之后的每天都从当天第一个符合的时刻开始
+        let mut time = Some(start.time());
+        let mut day = start.day();
+        let last = start.year().saturating_add(SEARCH_YEARS as i32);
+        let last = self.years.1.map_or(last, |end| end.min(last));
+        // 整月没有选中的日期时直接跳到下个有选中日期的月份
+        while let Some((found_year, found_month, mask)) = self.days.next_selected(year, month, last)
+        {
+            if (found_year, found_month) != (year, month) {
+                (year, month) = (found_year, found_month);
+                day = 1;
+                time = None;
+            }
+            let mut mask = mask & bits_from(day);
+            while let Some(selected) = lowest(mask) {
+                mask &= mask - 1;
+                let first = match time {
+                    Some(time) if selected == day as u64 => {
+                        self.first_time(time.hour(), time.minute(), time.second())
+                    }
+                    _ => self.first_time(0, 0, 0),
+                };
+                if let Some(first) = first {
+                    let date = NaiveDate::from_ymd_opt(year, month, selected as u32)?;
+                    return Some(NaiveDateTime::new(date, first));
+                }
+            }
+            (year, month) = add_months(year, month, 1);
+            day = 1;
+            time = None;
+        }
+        None
+    }
+
+    fn prev_with_time(&self, now: NaiveDateTime) -> Option<NaiveDateTime> {
+        if self.is_never() {
+            return None;
+        }
+        let start = now.checked_sub_signed(Duration::seconds(1))?;
+        let (mut year, mut month) = (start.year(), start.month());
+        // 只有第一天需要考虑当前时刻，之后的每天都从当天最后一个符合的时刻开始
+        let mut time = Some(start.time());
+        let mut day = start.day();
+        let first = start.year().saturating_sub(SEARCH_YEARS as i32);
+        let first = self.years.0.map_or(first, |start| start.max(first));
+        // 整月没有选中的日期时直接跳到上个有选中日期的月份
+        while let Some((found_year, found_month, mask)) =
+            self.days.prev_selected(year, month, first)
+        {
+            if (found_year, found_month) != (year, month) {
+                (year, month) = (found_year, found_month);
+                day = month_len(year, month);
+                time = None;
+            }
+            let mut mask = mask & bits_to(day);
+            while let Some(selected) = highest(mask) {
+                mask &= !(1 << selected);
+                let last = match time {
+                    Some(time) if selected == day as u64 => {
+                        self.last_time(time.hour(), time.minute(), time.second())
+                    }
+                    _ => self.last_time(23, 59, 59),
+                };
+                if let Some(last) = last {
+                    let date = NaiveDate::from_ymd_opt(year, month, selected as u32)?;
+                    return Some(NaiveDateTime::new(date, last));
+                }
+            }
+            (year, month) = add_months(year, month, -1);
+            day = month_len(year, month);
+            time = None;
+        }
+        None
+    }
+
+    fn matches(&self, datetime: NaiveDateTime) -> bool {
+        CompiledTimer::matches(self, datetime)
+    }
+
+    /// 时刻全选时按月查找没选中的日期；否则选中的日期内就有不符合的时刻，最多查看两天
+    fn next_unmatched(&self, now: NaiveDateTime) -> Option<NaiveDateTime> {
+        let start = now.checked_add_signed(Duration::seconds(1))?;
+        if self.is_never() {
+            return Some(start);
+        }
+        let all_day = self.is_all_day();
+        let (mut year, mut month) = (start.year(), start.month());
+        // 只有第一天需要考虑当前时刻，之后的每天都从0点开始
+        let mut time = Some(start.time());
+        let mut day = start.day();
+        let last = start.year().saturating_add(SEARCH_YEARS as i32);
+        while year <= last {
+            // 时刻全选时，整月的日期都选中则直接跳到下个有没选中日期的月份
+            if all_day {
+                let (found_year, found_month, _) = self.days.next_unselected(year, month, last)?;
+                if (found_year, found_month) != (year, month) {
+                    (year, month) = (found_year, found_month);
+                    day = 1;
+                    time = None;
+                }
+            }
+            let len = month_len(year, month);
+            let unselected = !self.month_mask(year, month) & bits_to(len) & bits_from(day);
+            let gap = if all_day {
+                lowest(unselected).map(|selected| match time {
+                    Some(time) if selected == day as u64 => (selected as u32, time),
+                    _ => (selected as u32, NaiveTime::MIN),
+                })
+            } else {
+                (day..=len).find_map(|selected| {
+                    let gap = match time {
+                        Some(time) if selected == day => match unselected & (1 << selected) {
+                            0 => self.first_gap(time.hour(), time.minute(), time.second()),
+                            _ => Some(time),
+                        },
+                        _ => match unselected & (1 << selected) {
+                            0 => self.first_gap(0, 0, 0),
+                            _ => Some(NaiveTime::MIN),
+                        },
+                    };
+                    gap.map(|gap| (selected, gap))
+                })
+            };
+            if let Some((selected, gap)) = gap {
+                let date = NaiveDate::from_ymd_opt(year, month, selected)?;
+                return Some(NaiveDateTime::new(date, gap));
+            }
+            (year, month) = add_months(year, month, 1);
+            day = 1;
+            time = None;
+        }
+        None
+    }
+
+    fn prev_unmatched(&self, now: NaiveDateTime) -> Option<NaiveDateTime> {
+        let start = now.checked_sub_signed(Duration::seconds(1))?;
+        if self.is_never() {
+            return Some(start);
+        }
+        let all_day = self.is_all_day();
+        let last_second = NaiveTime::from_hms(23, 59, 59);
+        let (mut year, mut month) = (start.year(), start.month());
+        // 只有第一天需要考虑当前时刻，之后的每天都从当天最后一秒开始
+        let mut time = Some(start.time());
+        let mut day = start.day();
+        let first = start.year().saturating_sub(SEARCH_YEARS as i32);
+        while year >= first {
+            // 时刻全选时，整月的日期都选中则直接跳到上个有没选中日期的月份
+            if all_day {
+                let (found_year, found_month, _) = self.days.prev_unselected(year, month, first)?;
+                if (found_year, found_month) != (year, month) {
+                    (year, month) = (found_year, found_month);
+                    day = month_len(year, month);
+                    time = None;
+                }
+            }
+            let unselected = !self.month_mask(year, month) & bits_to(day) & bits_from(1);
+            let gap = if all_day {
+                highest(unselected).map(|selected| match time {
+                    Some(time) if selected == day as u64 => (selected as u32, time),
+                    _ => (selected as u32, last_second),
+                })
+            } else {
+                (1..=day).rev().find_map(|selected| {
+                    let gap = match time {
+                        Some(time) if selected == day => match unselected & (1 << selected) {
+                            0 => self.last_gap(time.hour(), time.minute(), time.second()),
+                            _ => Some(time),
+                        },
+                        _ => match unselected & (1 << selected) {
+                            0 => self.last_gap(23, 59, 59),
+                            _ => Some(last_second),
+                        },
+                    };
+                    gap.map(|gap| (selected, gap))
+                })
+            };
+            if let Some((selected, gap)) = gap {
+                let date = NaiveDate::from_ymd_opt(year, month, selected)?;
+                return Some(NaiveDateTime::new(date, gap));
+            }
+            (year, month) = add_months(year, month, -1);
+            day = month_len(year, month);
+            time = None;
+        }
+        None
+    }
+}
+
+impl From<&TimerConf> for CompiledTimer {
+    fn from(conf: &TimerConf) -> Self {
+        CompiledTimer::new(conf)
+    }
+}
+
+impl From<TimerConf> for CompiledTimer {
+    fn from(conf: TimerConf) -> Self {
+        CompiledTimer::new(&conf)
+    }
+}
+
+fn to_time(hour: u64, minuter: u64, second: u64) -> NaiveTime {
+    NaiveTime::from_hms(hour as u32, minuter as u32, second as u32)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::builder::DayConfBuilder;
+    use crate::conf::test::datetime;
+    use crate::*;
+    use chrono::{Duration, NaiveDateTime};
+
+    #[test]
+    fn test_compiled_timer() -> anyhow::Result<()> {
+        let confs = vec![
+            configure_monthday(MonthDays::default_array(&[D1, D15, D29, D31]))
+                .build_with_hours(Hours::default_array(&[H0, H12, H23]))
+                .build_with_minuter(Minuters::every(20)?)
+                .build_with_second(Seconds::default_array(&[S0, S59])),
+            configure_weekday(WeekDays::default_array(&[W1, W5]))
+                .conf_month_days(MonthDays::default_value(D13))
+                .conf_mode(DaysMode::And)
+                .build_with_hours(Hours::default_range_step(H8..=H20, 3)?)
+                .build_with_minuter(Minuters::default_value(M30))
+                .build_with_second(Seconds::default_value(S0)),
+            configure_weekday(WeekDays::default_value(W1))
+                .conf_month_weeks(MonthWeeks::default_array(&[
+                    MonthWeek::First,
+                    MonthWeek::Last,
+                ]))
+                .build_with_hours(Hours::default_value(H9))
+                .build_with_minuter(Minuters::default_value(M0))
+                .build_with_second(Seconds::default_value(S0)),
+            configure_yearday(YearDays::default_value(60)?.add_last())
+                .build_with_hours(Hours::default_value(H6))
+                .build_with_minuter(Minuters::default_array(&[M0, M45]))
+                .build_with_second(Seconds::default_value(S30)),
+            configure_workday(ChinaCalendar::bundled())
+                .build_with_hours(Hours::default_value(H9))
+                .build_with_minuter(Minuters::default_value(M0))
+                .build_with_second(Seconds::default_value(S0)),
+        ];
+        for conf in confs {
+            let compiled = conf.compile();
+            let mut now = datetime(2023, 1, 1, 0, 0, 0);
+            while now < datetime(2026, 1, 1, 0, 0, 0) {
+                assert_eq!(
+                    compiled.next_with_time(now),
+                    Some(conf.next_with_time(now)),
+                    "{:?} {}",
+                    conf,
+                    now
+                );
+                assert_eq!(
+                    compiled.prev_with_time(now),
+                    Some(conf.prev_with_time(now)),
+                    "{:?} {}",
+                    conf,
+                    now
+                );
+                let next = conf.next_with_time(now);
+                assert!(compiled.matches(next));
+                assert!(
+                    !compiled.matches(next + Duration::seconds(1))
+                        || compiled.next_with_time(next) == Some(next + Duration::seconds(1))
+                );
+                now += Duration::seconds(7 * 3600 + 13 * 60 + 17);
+            }
+        }
+        // 永远不会触发
+        let conf = configure_monthday(MonthDays::default_value(D1))
+            .build_with_hours(Hours::default_value(H0))
+            .build_with_minuter(Minuters::default())
+            .build_with_second(Seconds::default_value(S0));
+        assert_eq!(
+            conf.compile().next_with_time(datetime(2023, 1, 1, 0, 0, 0)),
+            None
+        );
+        Ok(())
+    }
+    #[test]
+    fn test_compiled_edges() -> anyhow::Result<()> {
+        let at = |days: DayConfBuilder| {
+            days.build_with_hours(Hours::default_value(H23))
+                .build_with_minuter(Minuters::default_value(M59))
+                .build_with_second(Seconds::default_value(S59))
+        };
+        let confs = [
+            // 只有31天的月份
+            at(configure_monthday(MonthDays::default_value(D31))),
+            // 2月只有闰年有29日，2100年不是闰年
+            at(configure_monthday(MonthDays::default_value(D29))),
+            // 闰年的12月31日
+            at(configure_yearday(YearDays::default_value(366)?)),
+            // 按日历行划分、周日开始的最后一周的周六
+            at(configure_weekday(WeekDays::default_value(W6))
+                .conf_calendar_weeks(MonthWeeks::default_value(MonthWeek::Last))
+                .conf_week_start(WeekStart::Sunday)),
+            // 农历正月初一：农历表之外的年份没有选中的日期
+            at(configure_lunar(LunarDays::default_value(
+                LunarMonth::L1,
+                D1,
+            ))),
+        ];
+        let starts = [
+            datetime(1900, 1, 1, 0, 0, 0),
+            datetime(2023, 12, 31, 23, 59, 59),
+            datetime(2024, 1, 1, 0, 0, 0),
+            datetime(2024, 2, 29, 23, 59, 58),
+            datetime(2024, 2, 29, 23, 59, 59),
+            datetime(2024, 3, 1, 0, 0, 0),
+            datetime(2100, 2, 28, 23, 59, 59),
+            datetime(2101, 12, 31, 23, 59, 59),
+            datetime(2150, 6, 15, 12, 0, 0),
+        ];
+        for conf in confs.iter() {
+            let compiled = conf.compile();
+            for now in starts {
+                assert_eq!(
+                    compiled.next_with_time(now),
+                    Schedule::next_with_time(conf, now),
+                    "{:?} {}",
+                    conf,
+                    now
+                );
+                assert_eq!(
+                    compiled.prev_with_time(now),
+                    Schedule::prev_with_time(conf, now),
+                    "{:?} {}",
+                    conf,
+                    now
+                );
+            }
+        }
+        let compiled = confs[1].compile();
+        assert_eq!(
+            compiled.next_with_time(datetime(2100, 1, 29, 23, 59, 59)),
+            Some(datetime(2100, 3, 29, 23, 59, 59))
+        );
+        // 每年第366天只在闰年，跳过2100年
+        let compiled = confs[2].compile();
+        assert_eq!(
+            compiled.next_with_time(datetime(2096, 12, 31, 23, 59, 59)),
+            Some(datetime(2104, 12, 31, 23, 59, 59))
+        );
+        // 农历表之外直接返回None
+        let compiled = confs[4].compile();
+        assert_eq!(compiled.next_with_time(datetime(2150, 1, 1, 0, 0, 0)), None);
+        assert_eq!(compiled.prev_with_time(datetime(1800, 1, 1, 0, 0, 0)), None);
+
+        // 每项都选了值，但组合起来没有日期
+        let never = configure_monthday(MonthDays::default_range(D1..=D7)?)
+            .conf_month_weeks(MonthWeeks::default_value(MonthWeek::Fifth))
+            .build_with_hours(Hours::default_all())
+            .build_with_minuter(Minuters::default_all())
+            .build_with_second(Seconds::default_all());
+        let compiled = never.compile();
+        let now = datetime(2024, 5, 1, 0, 0, 0);
+        assert_eq!(compiled.next_with_time(now), None);
+        assert_eq!(compiled.prev_with_time(now), None);
+        assert!(!compiled.matches(now));
+        assert_eq!(
+            compiled.next_unmatched(now),
+            Some(now + Duration::seconds(1))
+        );
+        assert_eq!(
+            compiled.prev_unmatched(now),
+            Some(now - Duration::seconds(1))
+        );
+
+        // 每天每秒都符合：没有不符合的时刻
+        let always = configure_monthday(MonthDays::default_all())
+            .build_with_hours(Hours::default_all())
+            .build_with_minuter(Minuters::default_all())
+            .build_with_second(Seconds::default_all());
+        let compiled = always.compile();
+        assert_eq!(compiled.next_unmatched(now), None);
+        assert_eq!(compiled.prev_unmatched(now), None);
+        assert_eq!(
+            compiled.next_with_time(now),
+            Some(now + Duration::seconds(1))
+        );
+
+        // chrono能表示的时间的两端
+        assert_eq!(compiled.next_with_time(NaiveDateTime::MAX), None);
+        assert_eq!(compiled.prev_with_time(NaiveDateTime::MIN), None);
+        Ok(())
+    }
+}
@@ -1,8 +1,16 @@
 use crate::conf::{Days, Hours, Minuters, MonthDays, Seconds};
-use crate::data::{Hour, Minuter, MonthDay, Second, WeekDay};
-use crate::traits::{AsBizData, Computer, FromData, ConfigOperator};
-use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+use crate::data::{Hour, Minuter, MonthDay, Second};
+use crate::traits::{
+    bits_from, bits_to, highest, lowest, AsBizData, Computer, ConfigOperator, FromData,
+};
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
 use log::debug;
+use std::collections::HashMap;
+use std::ops::Sub;
+use std::sync::{Arc, RwLock};
+
+/// 按配置逐月现算时查找的年数，更远的时间点交给预编译的[`CompiledTimer`](crate::CompiledTimer)
+pub(crate) const NEAR_YEARS: i32 = 1;
 
 #[derive(Debug)]
 pub struct TimeUnit<T: ConfigOperator> {
@@ -21,13 +29,15 @@ pub struct DayUnit {
     year: i32,
     // 最后的值
     month: u32,
-    days: Days,
+    days: MonthMasks,
     // monthdays: Option<MonthDays>,
     // weekdays: Option<WeekDays>,
     day: MonthDay,
     max: u32,
     conf: MonthDays,
     val: u32,
+    // 查找的最后一年，超过则认为近处没有，交给预编译的结果
+    end: i32,
 }
 
 impl Computer for DayUnit {
@@ -35,23 +45,18 @@ impl Computer for DayUnit {
     type DataTy = MonthDay;
 
     fn update_to_next_ring(&mut self) {
-        loop {
-            if self.month == 12 {
-                self.month = 1;
-                self.year += 1;
-            } else {
-                self.month += 1;
-            }
-            let date = NaiveDate::from_ymd(self.year, self.month, 1);
-            let next_month = next_month(self.year, self.month);
-            let weekday: WeekDay = date.weekday().into();
-            self.conf = self.days.month_days(weekday);
-            self.max = next_month.pred().day();
-            self.day = self.conf.min_val();
-            if self.day.as_data() as u32 <= self.max {
+        let (year, month) = add_months(self.year, self.month, 1);
+        // 按节假日调整后，整月都可能没有选中的日期，直接跳到下个有选中日期的月份
+        match self.days.next_selected(year, month, self.end) {
+            Some((year, month, mask)) => {
+                self.year = year;
+                self.month = month;
+                self.conf = to_month_days(mask);
+                self.max = month_len(year, month);
+                self.day = self.conf.min_val();
                 self.val = self.day.as_data() as u32;
-                break;
             }
+            None => self.year = self.end.saturating_add(1),
         }
     }
 
@@ -60,15 +65,9 @@ impl Computer for DayUnit {
     }
 
     fn next_val(&self) -> Option<Self::DataTy> {
-        if let Some(next) = self.conf.next(self.day) {
-            if next.as_data() > self.max as u64 {
-                None
-            } else {
-                Some(next)
-            }
-        } else {
-            None
-        }
+        self.conf
+            .next(self.day)
+            .filter(|next| next.as_data() <= self.max as u64)
     }
 
     fn min_val(&self) -> Self::DataTy {
@@ -137,20 +136,29 @@ impl DayUnit {
         month: u32,
         days: Days,
         day: MonthDay,
-        first_week_day: WeekDay,
         max: u32,
     ) -> Self {
-        let conf = days.month_days(first_week_day);
+        let end = match days.years() {
+            (_, Some(last)) => last.min(year + NEAR_YEARS),
+            _ => year + NEAR_YEARS,
+        };
+        let days = MonthMasks::new(days);
+        let conf = days.month_days(year, month);
         Self {
             year,
             month,
             days,
-            day: day.clone(),
+            day,
             max,
             conf,
             val: day.as_data() as u32,
+            end,
         }
     }
+    /// 是否已超出查找范围
+    fn is_exhausted(&self) -> bool {
+        self.year > self.end
+    }
 }
 #[derive(Debug)]
 pub struct Composition {
@@ -171,9 +179,8 @@ impl Composition {
         let year = now.year();
         let month = now.month();
         let day = MonthDay::from_data(now.day() as u64);
-        let first_week_day: WeekDay = NaiveDate::from_ymd(year, month, 1).weekday().into();
-        let max = next_month(year, month).pred().day();
-        let day_unit = DayUnit::new(year, month, days, day, first_week_day, max);
+        let max = month_len(year, month);
+        let day_unit = DayUnit::new(year, month, days, day, max);
         let hour: TimeUnit<Hours> = TimeUnit::new(Hour::from_data(now.hour() as u64), hours);
         let minuter = TimeUnit::new(Minuter::from_data(now.minute() as u64), min);
         let second = TimeUnit::new(Second::from_data(now.second() as u64), seconds);
@@ -193,32 +200,30 @@ impl Composition {
         }
     }
 
-    pub fn next(&mut self) -> NaiveDateTime {
+    /// 下个符合的时间点，到明年年底都没有则为None。时、分、秒均不能为空
+    pub fn next(&mut self) -> Option<NaiveDateTime> {
         loop {
-            if self.day.is_match() {
-                if self.match_hour() {
-                    break;
-                }
+            if self.day.is_match() && self.match_hour() {
+                break;
             }
             self.next_day();
+            if self.day.is_exhausted() {
+                return None;
+            }
         }
         self.to_datetime()
     }
-    fn to_datetime(&self) -> NaiveDateTime {
-        NaiveDateTime::new(
-            NaiveDate::from_ymd(self.day.year, self.day.month, self.day.val),
-            NaiveTime::from_hms(
-                self.hour.val as u32,
-                self.minuter.val as u32,
-                self.second.val as u32,
-            ),
+    /// 超出chrono能表示的日期范围时为None
+    fn to_datetime(&self) -> Option<NaiveDateTime> {
+        NaiveDate::from_ymd_opt(self.day.year, self.day.month, self.day.val)?.and_hms_opt(
+            self.hour.val as u32,
+            self.minuter.val as u32,
+            self.second.val as u32,
         )
     }
     fn match_hour(&mut self) -> bool {
-        if self.hour.is_match() {
-            if self.match_minuter() {
-                return true;
-            }
+        if self.hour.is_match() && self.match_minuter() {
+            return true;
         }
         if let Some(hour) = self.hour.next_val() {
             self.hour.val_mut(hour);
@@ -229,10 +234,8 @@ impl Composition {
         }
     }
     fn match_minuter(&mut self) -> bool {
-        if self.minuter.is_match() {
-            if self.match_second() {
-                return true;
-            }
+        if self.minuter.is_match() && self.match_second() {
+            return true;
         }
         if let Some(minuter) = self.minuter.next_val() {
             self.minuter.val_mut(minuter);
@@ -257,10 +260,13 @@ impl Composition {
     fn next_day(&mut self) {
         if let Some(day) = self.day.next_val() {
             debug!("day_unit: {:?}, next_day: {:?}", self.day, day);
-            self.day.val_mut(day.clone());
+            self.day.val_mut(day);
             self.day.day = day;
         } else {
             self.day.update_to_next_ring();
+            if self.day.is_exhausted() {
+                return;
+            }
         }
         debug!("day_unit: {:?}", self.day);
         self.hour_update_to_next_ring();
@@ -278,15 +284,388 @@ impl Composition {
     }
 }
 
-pub fn next_month(mut year: i32, mut month: u32) -> NaiveDate {
-    if month == 12 {
-        month = 1;
-        year += 1;
-    } else {
-        month += 1;
+/// 以给定的时间点为起点(不包含该时点)，往前查找上个符合的时间点，到去年年初都没有则为None
+pub fn prev(
+    now: NaiveDateTime,
+    days: &Days,
+    hours: &Hours,
+    minuters: &Minuters,
+    seconds: &Seconds,
+) -> Option<NaiveDateTime> {
+    if hours.is_zero() || minuters.is_zero() || seconds.is_zero() {
+        return None;
+    }
+    let now = now.sub(Duration::seconds(1));
+    let (mut year, mut month, mut day) = (now.year(), now.month(), now.day());
+    let end = match days.years() {
+        (Some(first), _) => first.max(year - NEAR_YEARS),
+        _ => year - NEAR_YEARS,
+    };
+    let masks = MonthMasks::new(days.clone());
+    // 只有第一天需要考虑当前时刻，之后的每天都从当天最后一秒开始往前找
+    let mut time = Some(now.time());
+    // 整月没有选中的日期时直接跳到上个有选中日期的月份
+    while let Some((found_year, found_month, mut val)) = masks.prev_selected(year, month, end) {
+        if (found_year, found_month) != (year, month) {
+            (year, month) = (found_year, found_month);
+            day = month_len(year, month);
+            time = None;
+        }
+        // 当月不晚于day的选中日期，按位从后往前找
+        val &= bits_to(day);
+        while let Some(selected) = highest(val) {
+            val &= !(1 << selected);
+            let last = match time {
+                Some(time) if selected == day as u64 => last_time(time, hours, minuters, seconds),
+                _ => Some(to_time(hours.max_val(), minuters.max_val(), seconds.max_val())),
+            };
+            if let Some(last) = last {
+                let date = NaiveDate::from_ymd_opt(year, month, selected as u32)?;
+                return Some(NaiveDateTime::new(date, last));
+            }
+        }
+        (year, month) = add_months(year, month, -1);
+        day = month_len(year, month);
+        time = None;
+    }
+    None
+}
+
+/// 当天不晚于time的最后一个符合的时刻
+fn last_time(
+    time: NaiveTime,
+    hours: &Hours,
+    minuters: &Minuters,
+    seconds: &Seconds,
+) -> Option<NaiveTime> {
+    let hour = Hour::from_data(time.hour() as u64);
+    let minuter = Minuter::from_data(time.minute() as u64);
+    let second = Second::from_data(time.second() as u64);
+    if hours.contain(hour) {
+        if minuters.contain(minuter) {
+            if seconds.contain(second) {
+                return Some(time);
+            }
+            if let Some(second) = seconds.prev(second) {
+                return Some(to_time(hour, minuter, second));
+            }
+        }
+        if let Some(minuter) = minuters.prev(minuter) {
+            return Some(to_time(hour, minuter, seconds.max_val()));
+        }
+    }
+    hours
+        .prev(hour)
+        .map(|hour| to_time(hour, minuters.max_val(), seconds.max_val()))
+}
+
+fn to_time(hour: Hour, minuter: Minuter, second: Second) -> NaiveTime {
+    NaiveTime::from_hms(
+        hour.as_data() as u32,
+        minuter.as_data() as u32,
+        second.as_data() as u32,
+    )
+}
+
+/// 月份的形状：月份 × 是否闰年 × 1号是星期几，共168种。
+/// 同一形状的月份，天数、1号在当年的序数、每天是星期几都相同。
+/// 只看月日期、星期时，天数 × 1号是星期几就够了，但每年第几天（[`YearDays`](crate::YearDays)）
+/// 还取决于月份在一年中的位置以及是否闰年（闰年3月起的序数大1），因此按月份和是否闰年区分
+const SHAPES: usize = 168;
+// 还没算过的形状
+const UNKNOWN: u64 = u64::MAX;
+/// 公历400年共146097天，恰好是整周，月份形状每4800个月重复一次
+const CYCLE_MONTHS: usize = 4800;
+
+/// 按年记下的各月掩码
+type YearMasks = RwLock<HashMap<i32, [u64; 12]>>;
+
+/// 每月选中日期的掩码，第i位为1表示选中i号，不超过当月的天数；有多项日期配置时取交集。
+/// 日期只由月份形状决定时（见[`Days::is_month_shaped`]），逐月查看跨过一年后开始记下每种形状的结果，
+/// 之后再跳过多少月份，每种形状也只算一次；[`MonthMasks::fill`]还预先算好400年循环内每月是否有选中、
+/// 没选中的日期，查找时直接跳到要找的月份，与跳过多少月份无关。
+/// 否则（工作日、农历、节气、节假日调整、ISO周）每月现算；预先算好的版本第一次用到某年时整年算好记下
+#[derive(Debug, Clone)]
+pub(crate) struct MonthMasks {
+    // 同时符合所有配置才选中
+    days: Vec<Days>,
+    // 日期是否只由月份形状决定
+    shaped: bool,
+    // 没有记下结果、现算过的月数
+    computed: u32,
+    // 预先算好的每种形状都没有选中的日期
+    never: bool,
+    // 各形状的掩码，开始记下时才分配，近处的查找不必分配；日期不只由月份形状决定时一直为空
+    shapes: Vec<u64>,
+    // 循环内第i个月（从0年1月算起）有选中的日期则第i位为1，与shapes一起算好
+    selected: Vec<u64>,
+    // 循环内第i个月有没选中的日期则第i位为1
+    unselected: Vec<u64>,
+    // 日期不只由月份形状决定时，预先算好的版本按年记下用到过的各月掩码，克隆后共用
+    years: Option<Arc<YearMasks>>,
+}
+
+impl MonthMasks {
+    pub(crate) fn new(days: Days) -> Self {
+        Self::all(vec![days])
+    }
+    fn all(days: Vec<Days>) -> Self {
+        Self {
+            shaped: days.iter().all(Days::is_month_shaped),
+            days,
+            computed: 0,
+            never: false,
+            shapes: Vec::new(),
+            selected: Vec::new(),
+            unselected: Vec::new(),
+            years: None,
+        }
+    }
+    /// 预先算好每种形状，之后[`MonthMasks::mask`]只查表
+    pub(crate) fn fill(mut self) -> Self {
+        if !self.shaped {
+            self.years = Some(Default::default());
+            return self;
+        }
+        self.shapes = vec![UNKNOWN; SHAPES];
+        // 1901-2099年间28年一个周期，周期内每种形状都会出现
+        for year in 2000..2028 {
+            for month in 1..=12 {
+                self.mask_mut(year, month);
+            }
+        }
+        self.never = self.shapes.iter().all(|x| *x == 0);
+        self.selected = vec![0; CYCLE_MONTHS / 64];
+        self.unselected = vec![0; CYCLE_MONTHS / 64];
+        // 逐月推算1号是星期几，不必每月都按公式算形状
+        let mut first = shape(0, 1);
+        for index in 0..CYCLE_MONTHS {
+            let (year, month) = ((index / 12) as i32, (index % 12) as u32 + 1);
+            let (len, leap) = (month_len(year, month), is_leap_year(year) as usize);
+            let mask = self.shapes[(month as usize - 1) * 14 + leap * 7 + first];
+            if hits(mask, len, false) {
+                self.selected[index / 64] |= 1 << (index % 64);
+            }
+            if hits(mask, len, true) {
+                self.unselected[index / 64] |= 1 << (index % 64);
+            }
+            first = (first + len as usize) % 7;
+        }
+        self
+    }
+    /// 与other同时选中的日期，按[`MonthMasks::fill`]预先算好
+    pub(crate) fn intersection(&self, other: &MonthMasks) -> Self {
+        Self::all(self.days.iter().chain(other.days.iter()).cloned().collect()).fill()
+    }
+    /// 给定月份的掩码，算过的形状、记下的年份直接取
+    pub(crate) fn mask(&self, year: i32, month: u32) -> u64 {
+        match self.shapes.get(shape(year, month)) {
+            Some(mask) if *mask != UNKNOWN => *mask,
+            _ => match &self.years {
+                Some(years) => self.yearly(years, year)[month as usize - 1],
+                None => self.compute(year, month),
+            },
+        }
+    }
+    /// 给定年份各月的掩码，第一次用到时整年算好记下
+    fn yearly(&self, years: &YearMasks, year: i32) -> [u64; 12] {
+        if let Some(masks) = years.read().ok().and_then(|years| years.get(&year).copied()) {
+            return masks;
+        }
+        let masks = std::array::from_fn(|index| self.compute(year, index as u32 + 1));
+        if let Ok(mut years) = years.write() {
+            years.insert(year, masks);
+        }
+        masks
+    }
+    /// 给定月份的掩码，查找跨过一年后记下该形状的结果
+    pub(crate) fn mask_mut(&mut self, year: i32, month: u32) -> u64 {
+        if !self.shaped {
+            return self.compute(year, month);
+        }
+        if self.shapes.is_empty() {
+            if self.computed < 12 {
+                self.computed += 1;
+                return self.compute(year, month);
+            }
+            self.shapes = vec![UNKNOWN; SHAPES];
+        }
+        let index = shape(year, month);
+        if self.shapes[index] == UNKNOWN {
+            self.shapes[index] = self.compute(year, month);
+        }
+        self.shapes[index]
+    }
+    /// 不早于给定月份、有选中日期的第一个月及其掩码，晚于last年则为None
+    pub(crate) fn next_selected(
+        &self,
+        year: i32,
+        month: u32,
+        last: i32,
+    ) -> Option<(i32, u32, u64)> {
+        self.seek(year, month, last, 1, false)
+    }
+    /// 不晚于给定月份、有选中日期的最后一个月及其掩码，早于first年则为None
+    pub(crate) fn prev_selected(
+        &self,
+        year: i32,
+        month: u32,
+        first: i32,
+    ) -> Option<(i32, u32, u64)> {
+        self.seek(year, month, first, -1, false)
+    }
+    /// 不早于给定月份、有没选中日期的第一个月及其掩码，晚于last年则为None
+    pub(crate) fn next_unselected(
+        &self,
+        year: i32,
+        month: u32,
+        last: i32,
+    ) -> Option<(i32, u32, u64)> {
+        self.seek(year, month, last, 1, true)
+    }
+    /// 不晚于给定月份、有没选中日期的最后一个月及其掩码，早于first年则为None
+    pub(crate) fn prev_unselected(
+        &self,
+        year: i32,
+        month: u32,
+        first: i32,
+    ) -> Option<(i32, u32, u64)> {
+        self.seek(year, month, first, -1, true)
+    }
+    /// 从给定月份起按step逐月查找，直到超过bound年；预先算好时先按循环直接跳到要找的月份
+    fn seek(
+        &self,
+        mut year: i32,
+        mut month: u32,
+        bound: i32,
+        step: i32,
+        unselected: bool,
+    ) -> Option<(i32, u32, u64)> {
+        let cycle = if unselected { &self.unselected } else { &self.selected };
+        if !cycle.is_empty() {
+            let distance = match step {
+                1 => next_set(cycle, cycle_index(year, month))?,
+                _ => prev_set(cycle, cycle_index(year, month))?,
+            };
+            (year, month) = add_months(year, month, step * distance as i32);
+        }
+        while within(year, bound, step) {
+            let mask = self.mask(year, month);
+            if hits(mask, month_len(year, month), unselected) {
+                return Some((year, month, mask));
+            }
+            (year, month) = add_months(year, month, step);
+        }
+        None
+    }
+    pub(crate) fn month_days(&self, year: i32, month: u32) -> MonthDays {
+        to_month_days(self.mask(year, month))
+    }
+    /// 预先算好的每种形状都没有选中的日期，即永远不会选中
+    pub(crate) fn is_never(&self) -> bool {
+        self.never
+    }
+    fn compute(&self, year: i32, month: u32) -> u64 {
+        self.days
+            .iter()
+            .fold(bits_to(month_len(year, month)), |mask, days| {
+                mask & days.month_days(year, month)._val()
+            })
+    }
+}
+
+/// 当月是否有选中（unselected为true时：没选中）的日期
+fn hits(mask: u64, len: u32, unselected: bool) -> bool {
+    match unselected {
+        true => !mask & bits_to(len) & bits_from(1) != 0,
+        false => mask != 0,
     }
-    NaiveDate::from_ymd(year, month, 1)
 }
+
+/// 按step逐月查找时，year是否还没超过bound年
+fn within(year: i32, bound: i32, step: i32) -> bool {
+    match step {
+        1 => year <= bound,
+        _ => year >= bound,
+    }
+}
+
+/// 月份在400年循环内的序号
+fn cycle_index(year: i32, month: u32) -> usize {
+    year.rem_euclid(400) as usize * 12 + month as usize - 1
+}
+
+/// 循环位图里不早于index的第一个为1的位，与index相隔的位数；都为0则为None
+fn next_set(bits: &[u64], index: usize) -> Option<usize> {
+    let (word, bit) = (index / 64, index % 64);
+    if let Some(found) = lowest(bits[word] & bits_from(bit as u32)) {
+        return Some(word * 64 + found as usize - index);
+    }
+    // 绕一圈回到index所在的字，只剩index之前的位
+    (1..=bits.len()).find_map(|offset| {
+        let at = (word + offset) % bits.len();
+        let val = match offset == bits.len() {
+            true => bits[at] & !bits_from(bit as u32),
+            false => bits[at],
+        };
+        lowest(val).map(|found| (at * 64 + found as usize + CYCLE_MONTHS - index) % CYCLE_MONTHS)
+    })
+}
+
+/// 循环位图里不晚于index的最后一个为1的位，与index相隔的位数；都为0则为None
+fn prev_set(bits: &[u64], index: usize) -> Option<usize> {
+    let (word, bit) = (index / 64, index % 64);
+    if let Some(found) = highest(bits[word] & bits_to(bit as u32)) {
+        return Some(index - word * 64 - found as usize);
+    }
+    // 绕一圈回到index所在的字，只剩index之后的位
+    (1..=bits.len()).find_map(|offset| {
+        let at = (word + bits.len() - offset) % bits.len();
+        let val = match offset == bits.len() {
+            true => bits[at] & !bits_to(bit as u32),
+            false => bits[at],
+        };
+        highest(val).map(|found| (index + CYCLE_MONTHS - at * 64 - found as usize) % CYCLE_MONTHS)
+    })
+}
+
+/// 给定月份加上delta个月
+pub(crate) fn add_months(year: i32, month: u32, delta: i32) -> (i32, u32) {
+    let months = year as i64 * 12 + month as i64 - 1 + delta as i64;
+    (months.div_euclid(12) as i32, months.rem_euclid(12) as u32 + 1)
+}
+
+fn to_month_days(mask: u64) -> MonthDays {
+    let mut month_days = MonthDays::_default();
+    month_days._val_mut(mask);
+    month_days
+}
+
+/// 月份形状的序号。逐月跳过时每月都要算，因此1号是星期几按Sakamoto的公式直接算，不构造日期
+fn shape(year: i32, month: u32) -> usize {
+    const OFFSETS: [i32; 12] = [0, 3, 2, 5, 0, 3, 5, 1, 4, 6, 2, 4];
+    let y = if month < 3 { year - 1 } else { year };
+    // 0为周日，换算为0为周一
+    let leap_days = y.div_euclid(4) - y.div_euclid(100) + y.div_euclid(400);
+    let sunday_first = y + leap_days + OFFSETS[month as usize - 1] + 1;
+    let first = (sunday_first + 6).rem_euclid(7) as usize;
+    ((month as usize - 1) * 2 + is_leap_year(year) as usize) * 7 + first
+}
+
+pub(crate) fn is_leap_year(year: i32) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+/// 当月的天数
+pub(crate) fn month_len(year: i32, month: u32) -> u32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -430,4 +809,188 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_prev_skip_months() {
+        let dt = |y, m, d, h, mi, se| NaiveDate::from_ymd(y, m, d).and_hms(h, mi, se);
+        // 4月没有31号，整月跳过
+        let conf = configure_monthday(MonthDays::default_value(D31))
+            .build_with_hours(Hours::default_array(&[H0, H12]))
+            .build_with_minuter(Minuters::default_value(M30))
+            .build_with_second(Seconds::default_array(&[S0, S30]));
+        assert_eq!(
+            conf.prev_with_time(dt(2022, 5, 31, 0, 30, 0)),
+            dt(2022, 3, 31, 12, 30, 30)
+        );
+        assert_eq!(
+            conf.prev_with_time(dt(2022, 5, 31, 12, 30, 0)),
+            dt(2022, 5, 31, 0, 30, 30)
+        );
+        assert_eq!(
+            conf.next_with_time(dt(2022, 3, 31, 12, 30, 30)),
+            dt(2022, 5, 31, 0, 30, 0)
+        );
+    }
+
+    #[test]
+    fn test_never_fires() {
+        let dt = |y, m, d| NaiveDate::from_ymd(y, m, d).and_hms(0, 0, 0);
+        // 日期一直没有选中：超出查找范围后返回None，而不是一直找下去
+        let confs = [
+            configure_monthday(MonthDays::default())
+                .build_with_hours(Hours::default_all())
+                .build_with_minuter(Minuters::default_all())
+                .build_with_second(Seconds::default_all()),
+            configure_weekday(WeekDays::default())
+                .build_with_hours(Hours::default_all())
+                .build_with_minuter(Minuters::default_all())
+                .build_with_second(Seconds::default_all()),
+            // 时分秒为空
+            configure_monthday(MonthDays::default_all())
+                .build_with_hours(Hours::default())
+                .build_with_minuter(Minuters::default_all())
+                .build_with_second(Seconds::default_all()),
+        ];
+        for conf in confs.iter() {
+            assert_eq!(Schedule::next_with_time(conf, dt(2022, 1, 1)), None);
+            assert_eq!(Schedule::prev_with_time(conf, dt(2022, 1, 1)), None);
+        }
+        // 查找范围内有符合的时间点
+        let conf = configure_monthday(MonthDays::default_value(D29))
+            .build_with_hours(Hours::default_value(H0))
+            .build_with_minuter(Minuters::default_value(M0))
+            .build_with_second(Seconds::default_value(S0));
+        assert_eq!(
+            Schedule::next_with_time(&conf, dt(2022, 1, 29)),
+            Some(dt(2022, 3, 29))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "never fires")]
+    fn test_never_fires_panic() {
+        let conf = configure_monthday(MonthDays::default())
+            .build_with_hours(Hours::default_all())
+            .build_with_minuter(Minuters::default_all())
+            .build_with_second(Seconds::default_all());
+        conf.next_with_time(NaiveDate::from_ymd(2022, 1, 1).and_hms(0, 0, 0));
+    }
+    #[test]
+    fn test_date_limits() {
+        // 下个1号已超出chrono能表示的日期范围，返回None而不是panic
+        let conf = configure_monthday(MonthDays::default_value(D1))
+            .build_with_hours(Hours::default_value(H0))
+            .build_with_minuter(Minuters::default_value(M0))
+            .build_with_second(Seconds::default_value(S0));
+        let last = NaiveDate::MAX.and_hms_opt(0, 0, 0).unwrap();
+        assert_eq!(Schedule::next_with_time(&conf, last), None);
+        assert_eq!(Schedule::next_with_time(&conf.compile(), last), None);
+    }
+    #[test]
+    fn test_month_masks() {
+        let confs = [
+            configure_monthday(MonthDays::default_array(&[D1, D29, D31])),
+            configure_weekday(WeekDays::default_value(W5))
+                .conf_calendar_weeks(MonthWeeks::default_value(MonthWeek::Last))
+                .conf_week_start(WeekStart::Sunday),
+            configure_yearday(YearDays::default_array(&[60, 366]).unwrap()),
+            // 不由月份形状决定，不记下结果
+            configure_weekday(WeekDays::default_value(W1))
+                .conf_iso_weeks(IsoWeeks::default_value(IsoWeek::Wk53)),
+        ];
+        for conf in confs {
+            let days = conf
+                .build_with_hours(Hours::default_all())
+                .build_with_minuter(Minuters::default_all())
+                .build_with_second(Seconds::default_all())
+                .days;
+            let filled = MonthMasks::new(days.clone()).fill();
+            let mut masks = MonthMasks::new(days.clone());
+            // 按形状记下的结果与每月现算的相同
+            for year in 1900..2300 {
+                for month in 1..=12 {
+                    let first = NaiveDate::from_ymd(year, month, 1).weekday();
+                    assert_eq!(shape(year, month) % 7, first.num_days_from_monday() as usize);
+                    let expected =
+                        days.month_days(year, month)._val() & bits_to(month_len(year, month));
+                    assert_eq!(filled.mask(year, month), expected, "{}-{}", year, month);
+                    assert_eq!(masks.mask_mut(year, month), expected, "{}-{}", year, month);
+                }
+            }
+            assert_eq!(masks.shapes.is_empty(), !days.is_month_shaped());
+            // 不由月份形状决定时，预先算好的版本记下了查过的每一年
+            let years = filled.years.as_ref().map(|years| years.read().unwrap().len());
+            assert_eq!(years, (!days.is_month_shaped()).then_some(400));
+            assert!(!filled.is_never());
+        }
+        assert!(MonthMasks::new(Days::MonthDays(MonthDays::default()))
+            .fill()
+            .is_never());
+    }
+    #[test]
+    fn test_seek_months() {
+        let confs = [
+            // 只有闰年的12月有选中的日期
+            configure_yearday(YearDays::default_value(366).unwrap()),
+            // 每月都没有没选中的日期
+            configure_monthday(MonthDays::default_all()),
+            configure_monthday(MonthDays::default()),
+            // 不由月份形状决定，逐月现算
+            configure_weekday(WeekDays::default_value(W1))
+                .conf_iso_weeks(IsoWeeks::default_value(IsoWeek::Wk53)),
+        ];
+        for conf in confs {
+            let days = conf
+                .build_with_hours(Hours::default_all())
+                .build_with_minuter(Minuters::default_all())
+                .build_with_second(Seconds::default_all())
+                .days;
+            let filled = MonthMasks::new(days.clone()).fill();
+            // 逐月现算的结果
+            let scan = |mut year, mut month, bound, step, unselected| {
+                while within(year, bound, step) {
+                    let mask = filled.compute(year, month);
+                    if hits(mask, month_len(year, month), unselected) {
+                        return Some((year, month, mask));
+                    }
+                    (year, month) = add_months(year, month, step);
+                }
+                None
+            };
+            for year in 1995..2005 {
+                for month in 1..=12 {
+                    for span in [1, 10] {
+                        let (last, first) = (year + span, year - span);
+                        assert_eq!(
+                            filled.next_selected(year, month, last),
+                            scan(year, month, last, 1, false)
+                        );
+                        assert_eq!(
+                            filled.prev_selected(year, month, first),
+                            scan(year, month, first, -1, false)
+                        );
+                        assert_eq!(
+                            filled.next_unselected(year, month, last),
+                            scan(year, month, last, 1, true)
+                        );
+                        assert_eq!(
+                            filled.prev_unselected(year, month, first),
+                            scan(year, month, first, -1, true)
+                        );
+                    }
+                }
+            }
+        }
+    }
+    #[test]
+    fn test_empty_min_val() {
+        assert_eq!(Hours::default()._min_val(), None);
+        assert_eq!(Hours::default()._max_val(), None);
+        assert_eq!(Hours::default().first(), None);
+        let result = std::panic::catch_unwind(|| Minuters::default().min_val());
+        let message = result.unwrap_err().downcast::<String>().unwrap();
+        assert_eq!(*message, "no minuter is selected");
+        let result = std::panic::catch_unwind(|| WeekDays::default().max_val());
+        let message = result.unwrap_err().downcast::<String>().unwrap();
+        assert_eq!(*message, "no week day is selected");
+    }
 }
@@ -1,11 +1,20 @@
-use crate::compute::Composition;
-use crate::data::{Hour, Minuter, MonthDay, Second, WeekDay};
-use crate::traits::{FromData, ConfigOperator};
-use anyhow::{bail, Result};
-use chrono::{Datelike, Duration, Local, NaiveDateTime, Timelike};
+use crate::calendar::{self, HolidayCalendar, HolidayPolicy};
+use crate::compiled::CompiledTimer;
+use crate::compute::{self, month_len, Composition};
+use crate::data::{
+    Hour, IsoWeek, LunarMonth, Minuter, MonthDay, MonthWeek, Second, SolarTerm, WeekDay, WeekStart,
+};
+use crate::lunar::{self, LunarDays};
+use crate::solar_term;
+use crate::schedule::Schedule;
+use crate::traits::{AsBizData, FromData, ConfigOperator};
+use crate::error::{Error, Result};
+use chrono::{Datelike, Duration, Local, NaiveDate, NaiveDateTime, TimeZone, Timelike};
 use log::debug;
-use std::fmt::{Debug, Formatter};
-use std::ops::{Add, Bound, RangeBounds, Sub};
+use std::fmt::{Debug, Display, Formatter};
+use std::ops::{Add, Bound, RangeBounds};
+use std::str::FromStr;
+use std::sync::{Arc, OnceLock};
 
 /// 定时器配置
 #[derive(Debug, Clone)]
@@ -14,51 +23,99 @@ pub struct TimerConf {
     pub(crate) hours: Hours,
     pub(crate) minuters: Minuters,
     pub(crate) seconds: Seconds,
+    // 查找远处的时间点、不符合的时段时用到的预编译结果，第一次用到时生成
+    compiled: OnceLock<CompiledTimer>,
 }
 
 impl TimerConf {
+    pub(crate) fn new(days: Days, hours: Hours, minuters: Minuters, seconds: Seconds) -> Self {
+        Self {
+            days,
+            hours,
+            minuters,
+            seconds,
+            compiled: OnceLock::new(),
+        }
+    }
 
     /// 在给定的日期时间范围内，返回符合定时器的所有时间点
     pub fn datetimes(&self, range: impl RangeBounds<NaiveDateTime>) -> Result<Vec<NaiveDateTime>> {
-        // 转成 a..=b
-        let mut start = match range.start_bound() {
-            Bound::Unbounded => bail!("不支持该模式"),
-            Bound::Included(first) => first.sub(Duration::seconds(1)),
-            Bound::Excluded(first) => first.clone(),
-        };
-        let end = match range.end_bound() {
-            Bound::Unbounded => bail!("不支持该模式"),
-            Bound::Included(end) => end.clone(),
-            Bound::Excluded(end) => end.sub(Duration::seconds(1)),
-        };
-        if start >= end {
-            bail!("起始-结束日期配置错误")
-        }
-        let mut date_times = Vec::new();
-        while start <= end {
-            let next = self.next_with_time(start);
-            if next <= end {
-                date_times.push(next);
-                start = next;
-            } else {
-                break;
-            }
-        }
-        Ok(date_times)
+        Schedule::datetimes(self, range)
     }
-    /// 以给定的时间点为起点(不包含该时点)，返回下个符合定时器的时间点
+    /// 以给定的时间点为起点(不包含该时点)，返回下个符合定时器的时间点。
+    /// 400年内都没有符合的时间点时panic，可用[`Schedule::next_with_time`]得到None
     pub fn next_with_time(&self, now: NaiveDateTime) -> NaiveDateTime {
-        let now = now.add(Duration::seconds(1));
+        self.find_next(now).expect("the timer never fires within 400 years")
+    }
+    /// 到明年年底都没有时，交给预编译的结果查找，编译一次后复用，与下个时间点有多远无关
+    pub(crate) fn find_next(&self, now: NaiveDateTime) -> Option<NaiveDateTime> {
+        if self.hours.is_zero() || self.minuters.is_zero() || self.seconds.is_zero() {
+            return None;
+        }
         let mut composition = Composition::from(
-            now,
+            now.add(Duration::seconds(1)),
             self.days.clone(),
             self.hours.clone(),
             self.minuters.clone(),
             self.seconds.clone(),
         );
         debug!("Composition: {:?}", composition);
-        let next = composition.next();
-        next
+        composition
+            .next()
+            .or_else(|| self.compiled.get_or_init(|| self.compile()).next_with_time(now))
+    }
+    /// 到去年年初都没有时，同[`TimerConf::find_next`]交给预编译的结果查找
+    fn find_prev(&self, now: NaiveDateTime) -> Option<NaiveDateTime> {
+        compute::prev(now, &self.days, &self.hours, &self.minuters, &self.seconds)
+            .or_else(|| self.compiled.get_or_init(|| self.compile()).prev_with_time(now))
+    }
+    /// 检查定时器是否会触发：时、分、秒有一项没有选中任何值，
+    /// 或者在可能有选中日期的年份内（不限时从1900年起）400年都找不到选中的日期，返回[`Error::Unsatisfiable`]
+    pub fn validate(&self) -> Result<()> {
+        if self.hours.is_zero() {
+            return Err(Error::Unsatisfiable { field: Hours::NAME });
+        }
+        if self.minuters.is_zero() {
+            return Err(Error::Unsatisfiable {
+                field: Minuters::NAME,
+            });
+        }
+        if self.seconds.is_zero() {
+            return Err(Error::Unsatisfiable {
+                field: Seconds::NAME,
+            });
+        }
+        // 公历400年一个周期，周期内没有选中的日期就永远不会有
+        let first = self.days.years().0.unwrap_or(1900);
+        let start = NaiveDate::from_ymd(first, 1, 1).and_hms(0, 0, 0) - Duration::seconds(1);
+        match self.find_next(start) {
+            Some(_) => Ok(()),
+            None => Err(Error::Unsatisfiable { field: "day" }),
+        }
+    }
+    /// 给定的时间点是否符合定时器：日期、时、分、秒均被选中
+    pub fn matches(&self, datetime: NaiveDateTime) -> bool {
+        self.contains_date(datetime.date())
+            && self.hours.contain(Hour::from_data(datetime.hour() as u64))
+            && self.minuters.contain(Minuter::from_data(datetime.minute() as u64))
+            && self.seconds.contain(Second::from_data(datetime.second() as u64))
+    }
+    /// 按datetime所在时区的当地时间判断是否符合定时器
+    pub fn matches_tz<Tz: TimeZone>(&self, datetime: &chrono::DateTime<Tz>) -> bool {
+        self.matches(datetime.naive_local())
+    }
+    /// 给定的日期是否被选中（只看日期配置，不看时分秒）
+    pub fn contains_date(&self, date: NaiveDate) -> bool {
+        self.days.contain(date)
+    }
+    /// 预编译，用于反复查找的场景，见[`CompiledTimer`]
+    pub fn compile(&self) -> CompiledTimer {
+        CompiledTimer::new(self)
+    }
+    /// 以给定的时间点为起点(不包含该时点)，返回上个符合定时器的时间点。
+    /// 400年内都没有符合的时间点时panic，可用[`Schedule::prev_with_time`]得到None
+    pub fn prev_with_time(&self, now: NaiveDateTime) -> NaiveDateTime {
+        self.find_prev(now).expect("the timer never fires within 400 years")
     }
     /// 以当前时间点为起点，返回距离下个符合时间点的时间间隔（s）
     pub fn next(&self) -> u64 {
@@ -86,71 +143,413 @@ impl TimerConf {
         times
     }
 }
+impl Schedule for TimerConf {
+    fn next_with_time(&self, now: NaiveDateTime) -> Option<NaiveDateTime> {
+        self.find_next(now)
+    }
+    fn prev_with_time(&self, now: NaiveDateTime) -> Option<NaiveDateTime> {
+        self.find_prev(now)
+    }
+    fn matches(&self, datetime: NaiveDateTime) -> bool {
+        TimerConf::matches(self, datetime)
+    }
+    /// 组合计划（如[`Except`](crate::Except)）会反复调用，同样预编译一次后复用
+    fn next_unmatched(&self, now: NaiveDateTime) -> Option<NaiveDateTime> {
+        self.compiled.get_or_init(|| self.compile()).next_unmatched(now)
+    }
+    fn prev_unmatched(&self, now: NaiveDateTime) -> Option<NaiveDateTime> {
+        self.compiled.get_or_init(|| self.compile()).prev_unmatched(now)
+    }
+}
+/// 日期配置
 #[derive(Debug, Clone)]
+#[allow(clippy::enum_variant_names)]
 pub enum Days {
     MonthDays(MonthDays),
     WeekDays(WeekDays),
-    MonthAndWeekDays(MonthDays, WeekDays)
+    /// 月日期与星期，按DaysMode组合
+    MonthAndWeekDays(MonthDays, WeekDays, DaysMode),
+    /// 按节假日日历的工作日
+    WorkDays(Arc<dyn HolidayCalendar>),
+    /// 农历日期，如每年农历八月十五
+    LunarDays(LunarDays),
+    /// 节气当天，如清明、冬至
+    SolarTerms(SolarTerms),
+    /// 每年的第几天，如每年第100天、最后一天
+    YearDays(YearDays),
+    /// 符合其中任一配置即选中
+    Any(Vec<Days>),
+    /// 只保留days里落在月内第几周的日期，如“每月第1、3个周一”。
+    /// start为None时按日期划分（1-7号为第1周）；否则按日历的行划分：从start开始、包含1号的那周为第1周
+    MonthWeeks {
+        days: Box<Days>,
+        weeks: MonthWeeks,
+        start: Option<WeekStart>,
+    },
+    /// 只保留days里落在ISO周数的日期，如“单周的周五”。start为周日时按周日开始的周计算周数
+    IsoWeeks {
+        days: Box<Days>,
+        weeks: IsoWeeks,
+        start: WeekStart,
+    },
+    /// 按节假日日历调整选中的日期：跳过非工作日，或顺延/提前到工作日
+    Adjusted {
+        days: Box<Days>,
+        calendar: Arc<dyn HolidayCalendar>,
+        policy: HolidayPolicy,
+    },
 }
 
 impl Days {
-    pub(crate) fn month_days(&self, week_day: WeekDay) -> MonthDays {
+    /// 给定月份里被选中的日期
+    pub(crate) fn month_days(&self, year: i32, month: u32) -> MonthDays {
         match self {
             Days::MonthDays(month_days) => { month_days.clone()}
-            Days::WeekDays(week_days) => {week_days.to_month_days(week_day)}
-            Days::MonthAndWeekDays(month_days, week_days) => {
-                month_days.merge(&week_days.to_month_days(week_day))
+            Days::WeekDays(week_days) => {week_days.to_month_days(first_week_day(year, month))}
+            Days::MonthAndWeekDays(month_days, week_days, mode) => {
+                let week_days = week_days.to_month_days(first_week_day(year, month));
+                match mode {
+                    DaysMode::Or => month_days.merge(&week_days),
+                    DaysMode::And => month_days.intersection(&week_days),
+                }
+            }
+            Days::WorkDays(calendar) => calendar::workdays(calendar.as_ref(), year, month),
+            Days::LunarDays(lunar_days) => lunar_days.month_days(year, month),
+            Days::SolarTerms(solar_terms) => solar_terms.month_days(year, month),
+            Days::YearDays(year_days) => year_days.to_month_days(year, month),
+            Days::Any(days) => days
+                .iter()
+                .fold(MonthDays::_default(), |all, x| all.merge(&x.month_days(year, month))),
+            Days::MonthWeeks { days, weeks, start } => days
+                .month_days(year, month)
+                .intersection(&weeks.to_month_days(year, month, *start)),
+            Days::IsoWeeks { days, weeks, start } => days
+                .month_days(year, month)
+                .intersection(&weeks.to_month_days(year, month, *start)),
+            Days::Adjusted {
+                days,
+                calendar,
+                policy,
+            } => calendar::adjust(days, calendar.as_ref(), *policy, year, month),
+        }
+    }
+    /// 可能有选中日期的公历年份范围（两端都包含），None表示不限。
+    /// 农历、节气等按内置的表换算，超出表的年份不会有选中的日期，查找时不必再往后（前）找
+    pub(crate) fn years(&self) -> (Option<i32>, Option<i32>) {
+        match self {
+            Days::LunarDays(_) => {
+                let (first, last) = lunar::solar_years();
+                (Some(first), Some(last))
+            }
+            Days::SolarTerms(_) => {
+                let (first, last) = solar_term::years();
+                (Some(first), Some(last))
+            }
+            // 并集：有一项不限则不限
+            Days::Any(days) => days.iter().map(Days::years).fold(
+                (Some(i32::MAX), Some(i32::MIN)),
+                |(first, last), (x, y)| {
+                    (
+                        first.zip(x).map(|(a, b)| a.min(b)),
+                        last.zip(y).map(|(a, b)| a.max(b)),
+                    )
+                },
+            ),
+            Days::MonthWeeks { days, .. } | Days::IsoWeeks { days, .. } => days.years(),
+            // 顺延/提前可能跨年
+            Days::Adjusted { days, .. } => {
+                let (first, last) = days.years();
+                (first.map(|x| x - 1), last.map(|x| x + 1))
             }
+            _ => (None, None),
+        }
+    }
+    /// 日期依赖的节假日日历有安排的公历年份范围（两端都包含），多个日历取交集；
+    /// None表示不依赖节假日日历，或日历没有声明覆盖的年份
+    pub(crate) fn calendar_years(&self) -> Option<(i32, i32)> {
+        fn both(x: Option<(i32, i32)>, y: Option<(i32, i32)>) -> Option<(i32, i32)> {
+            match (x, y) {
+                (Some(x), Some(y)) => Some((x.0.max(y.0), x.1.min(y.1))),
+                (x, None) => x,
+                (None, y) => y,
+            }
+        }
+        let covered = |calendar: &Arc<dyn HolidayCalendar>| {
+            calendar.covered_years().map(|x| (*x.start(), *x.end()))
+        };
+        match self {
+            Days::WorkDays(calendar) => covered(calendar),
+            Days::Adjusted { days, calendar, .. } => both(days.calendar_years(), covered(calendar)),
+            Days::Any(days) => days.iter().map(Days::calendar_years).fold(None, both),
+            Days::MonthWeeks { days, .. } | Days::IsoWeeks { days, .. } => days.calendar_years(),
+            _ => None,
+        }
+    }
+    /// 给定的日期是否被选中
+    pub(crate) fn contain(&self, date: NaiveDate) -> bool {
+        match self {
+            // 单个日期直接换算，不必换算整月
+            Days::LunarDays(lunar_days) => lunar_days.contain(date),
+            _ => self
+                .month_days(date.year(), date.month())
+                .contain(MonthDay::from_data(date.day() as u64)),
         }
     }
     pub(crate) fn update_month_days(self, month_days: MonthDays) -> Self {
         match self {
             Days::MonthDays(_) => {Self::MonthDays(month_days)}
-            Days::WeekDays(week_days) => {Self::MonthAndWeekDays(month_days, week_days)}
-            Days::MonthAndWeekDays(_, week_days) => {Self::MonthAndWeekDays(month_days, week_days)}
+            Days::WeekDays(week_days) => {Self::MonthAndWeekDays(month_days, week_days, DaysMode::default())}
+            Days::MonthAndWeekDays(_, week_days, mode) => {Self::MonthAndWeekDays(month_days, week_days, mode)}
+            Days::Any(mut days) => {
+                match days.iter().position(Days::is_month_or_week_days) {
+                    Some(index) => {
+                        let old = days.remove(index);
+                        days.insert(index, old.update_month_days(month_days));
+                    }
+                    None => days.push(Self::MonthDays(month_days)),
+                }
+                Self::Any(days)
+            }
+            Days::MonthWeeks { days, weeks, start } => Self::MonthWeeks {
+                days: Box::new(days.update_month_days(month_days)),
+                weeks,
+                start,
+            },
+            Days::IsoWeeks {
+                days,
+                weeks,
+                start,
+            } => Self::IsoWeeks {
+                days: Box::new(days.update_month_days(month_days)),
+                weeks,
+                start,
+            },
+            Days::Adjusted {
+                days,
+                calendar,
+                policy,
+            } => Self::Adjusted {
+                days: Box::new(days.update_month_days(month_days)),
+                calendar,
+                policy,
+            },
+            other => Self::Any(vec![other, Self::MonthDays(month_days)]),
         }
     }
     pub(crate) fn update_week_days(self, week_days: WeekDays) -> Self {
         match self {
-            Days::MonthDays(month_days) => {Self::MonthAndWeekDays(month_days, week_days)}
+            Days::MonthDays(month_days) => {Self::MonthAndWeekDays(month_days, week_days, DaysMode::default())}
             Days::WeekDays(_) => {Self::WeekDays(week_days)}
-            Days::MonthAndWeekDays(month_days, _) => {Self::MonthAndWeekDays(month_days, week_days)}
+            Days::MonthAndWeekDays(month_days, _, mode) => {Self::MonthAndWeekDays(month_days, week_days, mode)}
+            Days::Any(mut days) => {
+                match days.iter().position(Days::is_month_or_week_days) {
+                    Some(index) => {
+                        let old = days.remove(index);
+                        days.insert(index, old.update_week_days(week_days));
+                    }
+                    None => days.push(Self::WeekDays(week_days)),
+                }
+                Self::Any(days)
+            }
+            Days::MonthWeeks { days, weeks, start } => Self::MonthWeeks {
+                days: Box::new(days.update_week_days(week_days)),
+                weeks,
+                start,
+            },
+            Days::IsoWeeks {
+                days,
+                weeks,
+                start,
+            } => Self::IsoWeeks {
+                days: Box::new(days.update_week_days(week_days)),
+                weeks,
+                start,
+            },
+            Days::Adjusted {
+                days,
+                calendar,
+                policy,
+            } => Self::Adjusted {
+                days: Box::new(days.update_week_days(week_days)),
+                calendar,
+                policy,
+            },
+            other => Self::Any(vec![other, Self::WeekDays(week_days)]),
+        }
+    }
+    /// 设置月日期与星期的组合方式
+    pub(crate) fn update_mode(self, mode: DaysMode) -> Self {
+        match self {
+            Days::MonthAndWeekDays(month_days, week_days, _) => {
+                Self::MonthAndWeekDays(month_days, week_days, mode)
+            }
+            Days::Any(days) => Self::Any(days.into_iter().map(|x| x.update_mode(mode)).collect()),
+            Days::MonthWeeks { days, weeks, start } => Self::MonthWeeks {
+                days: Box::new(days.update_mode(mode)),
+                weeks,
+                start,
+            },
+            Days::IsoWeeks {
+                days,
+                weeks,
+                start,
+            } => Self::IsoWeeks {
+                days: Box::new(days.update_mode(mode)),
+                weeks,
+                start,
+            },
+            Days::Adjusted {
+                days,
+                calendar,
+                policy,
+            } => Self::Adjusted {
+                days: Box::new(days.update_mode(mode)),
+                calendar,
+                policy,
+            },
+            other => other,
+        }
+    }
+    /// 设置周数计算时每周的第一天
+    pub(crate) fn update_week_start(self, start: WeekStart) -> Self {
+        match self {
+            Days::IsoWeeks { days, weeks, .. } => Self::IsoWeeks {
+                days: Box::new(days.update_week_start(start)),
+                weeks,
+                start,
+            },
+            Days::Any(days) => Self::Any(
+                days.into_iter()
+                    .map(|x| x.update_week_start(start))
+                    .collect(),
+            ),
+            Days::MonthWeeks {
+                days,
+                weeks,
+                start: week_start,
+            } => Self::MonthWeeks {
+                days: Box::new(days.update_week_start(start)),
+                weeks,
+                start: week_start.map(|_| start),
+            },
+            Days::Adjusted {
+                days,
+                calendar,
+                policy,
+            } => Self::Adjusted {
+                days: Box::new(days.update_week_start(start)),
+                calendar,
+                policy,
+            },
+            other => other,
+        }
+    }
+    fn is_month_or_week_days(&self) -> bool {
+        matches!(
+            self,
+            Days::MonthDays(_) | Days::WeekDays(_) | Days::MonthAndWeekDays(..)
+        )
+    }
+    /// 选中的日期是否按公历400年循环，即不依赖农历、节气、节假日等数据表
+    pub(crate) fn is_periodic(&self) -> bool {
+        match self {
+            Days::WorkDays(_) | Days::LunarDays(_) | Days::SolarTerms(_) | Days::Adjusted { .. } => {
+                false
+            }
+            Days::Any(days) => days.iter().all(Days::is_periodic),
+            Days::MonthWeeks { days, .. } | Days::IsoWeeks { days, .. } => days.is_periodic(),
+            _ => true,
+        }
+    }
+    /// 每月选中的日期是否只由月份形状决定：月份、是否闰年及1号是星期几
+    pub(crate) fn is_month_shaped(&self) -> bool {
+        match self {
+            Days::MonthDays(_)
+            | Days::WeekDays(_)
+            | Days::MonthAndWeekDays(..)
+            | Days::YearDays(_) => true,
+            Days::Any(days) => days.iter().all(Days::is_month_shaped),
+            Days::MonthWeeks { days, .. } => days.is_month_shaped(),
+            _ => false,
+        }
+    }
+}
+
+/// 同时配置了月日期与星期时的组合方式
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default)]
+pub enum DaysMode {
+    /// 符合其一即选中（与cron一致），如“每月1号及每个周一”
+    #[default]
+    Or,
+    /// 同时符合才选中，如“13号且是周五”
+    And,
+}
+
+/// 文本形式为`or`、`and`
+impl Display for DaysMode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DaysMode::Or => write!(f, "or"),
+            DaysMode::And => write!(f, "and"),
         }
     }
-    // pub(crate) fn is_zero(&self) -> bool {
-    //     match self {
-    //         Days::MonthDays(month_days) => month_days.is_zero(),
-    //         Days::WeekDays(week_days) => week_days.is_zero(),
-    //         Days::MonthAndWeekDays(month_days, week_days) => month_days.is_zero() || week_days.is_zero()
-    //     }
-    // }
 }
 
+/// 解析`or`、`and`（不区分大小写），与Display互逆
+impl FromStr for DaysMode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim() {
+            x if x.eq_ignore_ascii_case("or") => Ok(DaysMode::Or),
+            x if x.eq_ignore_ascii_case("and") => Ok(DaysMode::And),
+            x => Err(Error::parse_text(format!(
+                "expected `or` or `and`, found `{}`",
+                x
+            ))),
+        }
+    }
+}
+
+/// 当月1号是星期几
+pub(crate) fn first_week_day(year: i32, month: u32) -> WeekDay {
+    NaiveDate::from_ymd(year, month, 1).weekday().into()
+}
 
 /// 每月的天数配置。如配置（选中）1号、3号……29号
-#[derive(Clone)]
+#[derive(Clone, Default, Eq, PartialEq, Hash)]
 pub struct MonthDays(u64);
 /// 每星期的天数配置。如配置（选中）周一……周六
-#[derive(Clone)]
+#[derive(Clone, Default, Eq, PartialEq, Hash)]
 pub struct WeekDays(u64);
 /// 每天的小时（时钟）配置。如配置（选中）0点、3点、9点、……18点
-#[derive(Clone)]
+#[derive(Clone, Default, Eq, PartialEq, Hash)]
 pub struct Hours(u64);
+/// 农历月份配置。如配置（选中）正月、八月
+#[derive(Clone, Default, Eq, PartialEq, Hash)]
+pub struct LunarMonths(u64);
+/// 节气配置。如配置（选中）清明、冬至
+#[derive(Clone, Default, Eq, PartialEq, Hash)]
+pub struct SolarTerms(u64);
+/// 月内第几周的配置。如配置（选中）第1周、第3周、最后一周
+#[derive(Clone, Default, Eq, PartialEq, Hash)]
+pub struct MonthWeeks(u64);
+/// ISO周数的配置。如配置（选中）单周
+#[derive(Clone, Default, Eq, PartialEq, Hash)]
+pub struct IsoWeeks(u64);
 /// 每小时的分钟配置。如配置（选中）0分、5分……58分
-#[derive(Clone, Eq, PartialEq)]
+#[derive(Clone, Default, Eq, PartialEq, Hash)]
 pub struct Minuters(u64);
 /// 每分钟的秒钟配置。如配置（选中）0秒、5秒……58秒
-#[derive(Clone)]
+#[derive(Clone, Default, Eq, PartialEq, Hash)]
 pub struct Seconds(u64);
 impl ConfigOperator for Hours {
     const MIN: u64 = 0;
     const MAX: u64 = 23;
     const DEFAULT_MAX: u64 = (u32::MAX >> 8) as u64;
+    const NAME: &'static str = "hour";
     type DataTy = Hour;
 
-    fn min_val(&self) -> Self::DataTy {
-        Self::DataTy::from_data(self._min_val())
-    }
     fn _default() -> Self {
         Self(0)
     }
@@ -158,8 +557,7 @@ impl ConfigOperator for Hours {
         self.0
     }
     fn next(&self, index: Self::DataTy) -> Option<Self::DataTy> {
-        self._next(index)
-            .and_then(|x| Some(Self::DataTy::from_data(x)))
+        self._next(index).map(Self::DataTy::from_data)
     }
     fn _val_mut(&mut self, val: u64) {
         self.0 = val
@@ -170,13 +568,10 @@ impl ConfigOperator for Seconds {
     const MIN: u64 = 0;
     const MAX: u64 = 59;
     const DEFAULT_MAX: u64 = u64::MAX >> 4;
+    const NAME: &'static str = "second";
     type DataTy = Second;
-    fn min_val(&self) -> Self::DataTy {
-        Self::DataTy::from_data(self._min_val())
-    }
     fn next(&self, index: Self::DataTy) -> Option<Self::DataTy> {
-        self._next(index)
-            .and_then(|x| Some(Self::DataTy::from_data(x)))
+        self._next(index).map(Self::DataTy::from_data)
     }
     fn _default() -> Self {
         Self(0)
@@ -192,13 +587,10 @@ impl ConfigOperator for Minuters {
     const MIN: u64 = 0;
     const MAX: u64 = 59;
     const DEFAULT_MAX: u64 = u64::MAX >> 4;
+    const NAME: &'static str = "minuter";
     type DataTy = Minuter;
-    fn min_val(&self) -> Self::DataTy {
-        Self::DataTy::from_data(self._min_val())
-    }
     fn next(&self, index: Self::DataTy) -> Option<Self::DataTy> {
-        self._next(index)
-            .and_then(|x| Some(Self::DataTy::from_data(x)))
+        self._next(index).map(Self::DataTy::from_data)
     }
     fn _default() -> Self {
         Self(0)
@@ -215,16 +607,53 @@ impl ConfigOperator for MonthDays {
     const MIN: u64 = 1;
     const MAX: u64 = 31;
     const DEFAULT_MAX: u64 = (u32::MAX << 1) as u64;
+    const NAME: &'static str = "month day";
     type DataTy = MonthDay;
     fn next(&self, index: Self::DataTy) -> Option<Self::DataTy> {
-        self._next(index)
-            .and_then(|x| Some(Self::DataTy::from_data(x)))
+        self._next(index).map(Self::DataTy::from_data)
+    }
+    fn _default() -> Self {
+        Self(0)
+    }
+    fn _val(&self) -> u64 {
+        self.0
+    }
+    fn _val_mut(&mut self, val: u64) {
+        self.0 = val
+    }
+}
+
+impl ConfigOperator for LunarMonths {
+    const MIN: u64 = 1;
+    const MAX: u64 = 12;
+    const DEFAULT_MAX: u64 = (u16::MAX >> 4 << 1) as u64;
+    const NAME: &'static str = "lunar month";
+    type DataTy = LunarMonth;
+    fn next(&self, index: Self::DataTy) -> Option<Self::DataTy> {
+        self._next(index).map(Self::DataTy::from_data)
     }
     fn _default() -> Self {
         Self(0)
     }
-    fn min_val(&self) -> Self::DataTy {
-        Self::DataTy::from_data(self._min_val())
+    fn _val(&self) -> u64 {
+        self.0
+    }
+    fn _val_mut(&mut self, val: u64) {
+        self.0 = val
+    }
+}
+
+impl ConfigOperator for SolarTerms {
+    const MIN: u64 = 1;
+    const MAX: u64 = 24;
+    const DEFAULT_MAX: u64 = (u32::MAX >> 8 << 1) as u64;
+    const NAME: &'static str = "solar term";
+    type DataTy = SolarTerm;
+    fn next(&self, index: Self::DataTy) -> Option<Self::DataTy> {
+        self._next(index).map(Self::DataTy::from_data)
+    }
+    fn _default() -> Self {
+        Self(0)
     }
     fn _val(&self) -> u64 {
         self.0
@@ -234,24 +663,49 @@ impl ConfigOperator for MonthDays {
     }
 }
 
-impl Minuters {
-    pub fn every(interval: u64) -> Self {
-        if interval == 0 {
-            Self::_default()
-        } else {
-            let mut val = 0u64;
-            let mut minuters = Self::_default();
-            while val <= Self::MAX {
-                minuters = minuters.add(Minuter::from_data(val));
-                val += interval
-            }
-            minuters
-        }
+impl ConfigOperator for MonthWeeks {
+    const MIN: u64 = 1;
+    const MAX: u64 = 6;
+    const DEFAULT_MAX: u64 = (u8::MAX >> 2 << 1) as u64;
+    const NAME: &'static str = "month week";
+    type DataTy = MonthWeek;
+    fn next(&self, index: Self::DataTy) -> Option<Self::DataTy> {
+        self._next(index).map(Self::DataTy::from_data)
+    }
+    fn _default() -> Self {
+        Self(0)
+    }
+    fn _val(&self) -> u64 {
+        self.0
+    }
+    fn _val_mut(&mut self, val: u64) {
+        self.0 = val
+    }
+}
+
+impl ConfigOperator for IsoWeeks {
+    const MIN: u64 = 1;
+    const MAX: u64 = 53;
+    const DEFAULT_MAX: u64 = u64::MAX >> 11 << 1;
+    const NAME: &'static str = "iso week";
+    type DataTy = IsoWeek;
+    fn next(&self, index: Self::DataTy) -> Option<Self::DataTy> {
+        self._next(index).map(Self::DataTy::from_data)
+    }
+    fn _default() -> Self {
+        Self(0)
+    }
+    fn _val(&self) -> u64 {
+        self.0
+    }
+    fn _val_mut(&mut self, val: u64) {
+        self.0 = val
     }
 }
 
 impl ConfigOperator for WeekDays {
     const DEFAULT_MAX: u64 = (u8::MAX << 1) as u64;
+    const NAME: &'static str = "week day";
     const MIN: u64 = 1;
     const MAX: u64 = 7;
 
@@ -261,12 +715,8 @@ impl ConfigOperator for WeekDays {
         Self(0)
     }
 
-    fn min_val(&self) -> Self::DataTy {
-        Self::DataTy::from_data(self._min_val())
-    }
     fn next(&self, index: Self::DataTy) -> Option<Self::DataTy> {
-        self._next(index)
-            .and_then(|x| Some(Self::DataTy::from_data(x)))
+        self._next(index).map(Self::DataTy::from_data)
     }
 
     fn _val(&self) -> u64 {
@@ -278,8 +728,202 @@ impl ConfigOperator for WeekDays {
     }
 }
 
-/// 为啥不是实现Operator
-#[allow(dead_code)]
+impl MonthWeeks {
+    /// 给定月份里，落在选中的周内的日期。start为None时按日期划分，否则按从start开始的日历行划分
+    pub(crate) fn to_month_days(
+        &self,
+        year: i32,
+        month: u32,
+        start: Option<WeekStart>,
+    ) -> MonthDays {
+        let max = month_len(year, month) as u64;
+        // 1号之前属于第1周的天数
+        let weekday = NaiveDate::from_ymd(year, month, 1).weekday();
+        let offset = match start {
+            None => 0,
+            Some(WeekStart::Monday) => weekday.num_days_from_monday(),
+            Some(WeekStart::Sunday) => weekday.num_days_from_sunday(),
+        } as u64;
+        let mut days = 0u64;
+        for week in self.to_vec() {
+            let (first, last) = match (week == MonthWeek::Last.as_data(), start) {
+                (true, None) => (max - 6, max),
+                // 最后一个日历行：从包含最后一天的那周的第一天开始
+                (true, Some(_)) => (max - (max - 1 + offset) % 7, max),
+                (false, _) => (((week - 1) * 7 + 1).saturating_sub(offset), week * 7 - offset),
+            };
+            for day in first.max(1)..=last.min(max) {
+                days |= 1 << day;
+            }
+        }
+        let mut month_days = MonthDays::_default();
+        month_days._val_mut(days);
+        month_days
+    }
+}
+
+impl IsoWeeks {
+    /// 给定月份里，周数被选中的日期
+    pub(crate) fn to_month_days(&self, year: i32, month: u32, start: WeekStart) -> MonthDays {
+        let mut month_days = MonthDays::_default();
+        let mut date = NaiveDate::from_ymd(year, month, 1);
+        while date.month() == month {
+            if self.contain(IsoWeek::from_data(start.week_number(date) as u64)) {
+                month_days = month_days.add(MonthDay::from_data(date.day() as u64));
+            }
+            date = date.succ();
+        }
+        month_days
+    }
+}
+
+/// 每年的天数配置（1-366），及每年的最后一天。如配置（选中）第1天、第100天、最后一天。
+/// 366天超出了u64的位数，因此没有实现ConfigOperator，而是按同样的方式提供配置方法。
+/// 平年没有第366天，该天不触发
+#[derive(Clone, Default, Eq, PartialEq, Hash)]
+pub struct YearDays {
+    // 第i位为1表示选中第i天
+    days: [u64; 6],
+    // 是否选中每年的最后一天（平年的第365天、闰年的第366天）
+    last: bool,
+}
+
+impl YearDays {
+    pub const MIN: u16 = 1;
+    pub const MAX: u16 = 366;
+
+    pub fn default_value(day: u16) -> Result<Self> {
+        Self::default().add_day(day)
+    }
+    pub fn default_array(days: &[u16]) -> Result<Self> {
+        Self::default().add_array(days)
+    }
+    pub fn default_range(range: impl RangeBounds<u16>) -> Result<Self> {
+        Self::default().add_range(range)
+    }
+    /// 每年的最后一天
+    pub fn default_last() -> Self {
+        Self::default().add_last()
+    }
+    pub fn add_day(mut self, day: u16) -> Result<Self> {
+        if !(Self::MIN..=Self::MAX).contains(&day) {
+            return Err(Error::out_of_range(
+                "year day",
+                day as u64,
+                Self::MIN as u64,
+                Self::MAX as u64,
+            ));
+        }
+        self.days[day as usize / 64] |= 1 << (day % 64);
+        Ok(self)
+    }
+    pub fn add_array(self, days: &[u16]) -> Result<Self> {
+        days.iter().try_fold(self, |all, x| all.add_day(*x))
+    }
+    pub fn add_range(self, range: impl RangeBounds<u16>) -> Result<Self> {
+        let first = match range.start_bound() {
+            Bound::Unbounded => Self::MIN,
+            Bound::Included(first) => *first,
+            Bound::Excluded(first) => first.saturating_add(1),
+        };
+        let end = match range.end_bound() {
+            Bound::Unbounded => Self::MAX,
+            Bound::Included(end) => *end,
+            Bound::Excluded(end) => end.saturating_sub(1),
+        };
+        if first > end {
+            return Err(Error::empty_range("year day", first, end));
+        }
+        (first..=end).try_fold(self, |all, x| all.add_day(x))
+    }
+    /// 选中每年的最后一天
+    pub fn add_last(mut self) -> Self {
+        self.last = true;
+        self
+    }
+    pub fn contain(&self, day: u16) -> bool {
+        day <= Self::MAX && self.days[day as usize / 64] & (1 << (day % 64)) > 0
+    }
+    /// 是否选中每年的最后一天
+    pub fn contain_last(&self) -> bool {
+        self.last
+    }
+    pub fn to_vec(&self) -> Vec<u16> {
+        (Self::MIN..=Self::MAX).filter(|x| self.contain(*x)).collect()
+    }
+    /// 是否啥都没有选
+    pub fn is_zero(&self) -> bool {
+        !self.last && self.days.iter().all(|x| *x == 0)
+    }
+    /// 给定月份里被选中的日期。按当月1号在当年的序数换算，闰年3月起的序数比平年大1；
+    /// 查找时按月展开，与其他日期配置一样由DayUnit逐月处理
+    pub(crate) fn to_month_days(&self, year: i32, month: u32) -> MonthDays {
+        let first = NaiveDate::from_ymd(year, month, 1);
+        let ordinal = first.ordinal() as u16;
+        let len = month_len(year, month) as u16;
+        let mut days = 0u64;
+        for day in 1..=len {
+            if self.contain(ordinal + day - 1) {
+                days |= 1 << day;
+            }
+        }
+        // 12月31日即为当年的最后一天
+        if self.last && month == 12 {
+            days |= 1 << 31;
+        }
+        let mut month_days = MonthDays::_default();
+        month_days._val_mut(days);
+        month_days
+    }
+}
+
+impl WeekDays {
+    /// 按start约定的编号配置星期，如cron的0、6（周日、周六）
+    pub fn default_numbers(numbers: &[u64], start: WeekStart) -> Result<Self> {
+        let mut week_days = Self::_default();
+        for number in numbers {
+            week_days = week_days.add(start.parse(*number)?);
+        }
+        Ok(week_days)
+    }
+    /// 按start约定的编号及先后顺序，列出选中的星期
+    pub fn to_vec_by(&self, start: WeekStart) -> Vec<u64> {
+        start
+            .days()
+            .into_iter()
+            .filter(|x| self.contain(*x))
+            .map(|x| start.number(x))
+            .collect()
+    }
+    /// 按start约定的编号显示选中的星期，逗号分隔，如周日开始时周六、周日为“0,6”
+    pub fn to_string_by(&self, start: WeekStart) -> String {
+        let numbers: Vec<String> = self.to_vec_by(start).iter().map(u64::to_string).collect();
+        numbers.join(",")
+    }
+    /// 按start约定的编号解析星期，逗号分隔，可带区间，如cron的“1-5”、“0,6”
+    pub fn parse_by(text: &str, start: WeekStart) -> Result<Self> {
+        let number = |x: &str| {
+            x.trim()
+                .parse::<u64>()
+                .map_err(|_| Error::parse_text(format!("invalid week day `{}`", x)))
+        };
+        let mut week_days = Self::_default();
+        for item in text.split(',') {
+            let (first, last) = match item.split_once('-') {
+                Some((first, last)) => (number(first)?, number(last)?),
+                None => (number(item)?, number(item)?),
+            };
+            if first > last {
+                return Err(Error::empty_range(Self::NAME, first, last));
+            }
+            for number in first..=last {
+                week_days = week_days.add(start.parse(number)?);
+            }
+        }
+        Ok(week_days)
+    }
+}
+
 impl WeekDays {
     pub(crate) fn to_month_days(&self, start: WeekDay) -> MonthDays {
         // 因WeekDays起始位置为1,右移去掉冗余的0位
@@ -292,44 +936,8 @@ impl WeekDays {
         let mut month_days = MonthDays::_default();
         month_days._val_mut(days);
         month_days
-        //
-        //
-        // let mut next = Some(WeekArray::init(start));
-        // let conf_week_days = self.to_vec();
-        //
-        // let mut monthdays = MonthDays::_default();
-        // while let Some(ref weekday) = next {
-        //     for x in &conf_week_days {
-        //         if let Some(day) = weekday.day(*x as usize) {
-        //             monthdays = monthdays.add(MonthDay::from_data(day));
-        //         }
-        //     }
-        //     next = weekday.next();
-        // }
-        // monthdays
-    }
-}
-//
-// #[derive(Debug)]
-// pub struct NextTime {
-//     hours: u64,
-//     minuters: u64,
-//     seconds: u64,
-// }
-// impl NextTime {
-//     fn init(mut times: u64) -> Self {
-//         let seconds = times % 60;
-//         times = times / 60;
-//         let minuters = times % 60;
-//         times = times / 60;
-//         let hours = times % 60;
-//         Self {
-//             seconds,
-//             minuters,
-//             hours,
-//         }
-//     }
-// }
+    }
+}
 
 impl Debug for Seconds {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
@@ -367,6 +975,51 @@ impl Debug for MonthDays {
         }
     }
 }
+impl Debug for LunarMonths {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.0 == (u16::MAX >> 4 << 1) as u64 {
+            write!(f, "all lunar months.")
+        } else {
+            write!(f, "lunar months: {:?}.", self.to_vec())
+        }
+    }
+}
+impl Debug for SolarTerms {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.0 == (u32::MAX >> 8 << 1) as u64 {
+            write!(f, "all solar terms.")
+        } else {
+            write!(f, "solar terms: {:?}.", self.to_vec())
+        }
+    }
+}
+impl Debug for MonthWeeks {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.0 == (u8::MAX >> 2 << 1) as u64 {
+            write!(f, "all month weeks.")
+        } else {
+            write!(f, "month weeks: {:?}.", self.to_vec())
+        }
+    }
+}
+impl Debug for IsoWeeks {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.0 == u64::MAX >> 11 << 1 {
+            write!(f, "all iso weeks.")
+        } else {
+            write!(f, "iso weeks: {:?}.", self.to_vec())
+        }
+    }
+}
+impl Debug for YearDays {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.last {
+            write!(f, "year days: {:?} and the last day.", self.to_vec())
+        } else {
+            write!(f, "year days: {:?}.", self.to_vec())
+        }
+    }
+}
 impl Debug for WeekDays {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         if self.0 == (u8::MAX << 1) as u64 {
@@ -378,16 +1031,16 @@ impl Debug for WeekDays {
 }
 
 #[cfg(test)]
-mod test {
-    use super::{Hours, Minuters, MonthDays, ConfigOperator, Seconds, WeekDays};
+pub(crate) mod test {
+    use super::{DaysMode, Hours, IsoWeeks, Minuters, MonthDays, MonthWeeks, ConfigOperator, Seconds, WeekDays, YearDays};
     use crate::conf::TimerConf;
-    #[allow(unused_imports)]
-    use crate::data::{DateTime, Hour::*, Minuter::*, MonthDay::*, Second::*, WeekDay::*};
+    use crate::data::DateTime;
     use crate::*;
     use anyhow::Result;
     use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime};
     use log::debug;
     use std::ops::Sub;
+    use std::ops::Bound;
 
 
     pub(crate) fn datetime(
@@ -404,9 +1057,83 @@ mod test {
         )
     }
 
+    /// 逐秒判断now前后days天内的下个/上个不符合的时间点，与next_unmatched/prev_unmatched比较
+    pub(crate) fn check_unmatched(schedule: &impl Schedule, now: NaiveDateTime, days: i64) {
+        let step = Duration::seconds(1);
+        let mut next = now + step;
+        while schedule.matches(next) && next - now < Duration::days(days) {
+            next += step;
+        }
+        match schedule.matches(next) {
+            true => assert!(schedule.next_unmatched(now).map_or(true, |x| x > next), "{}", now),
+            false => assert_eq!(schedule.next_unmatched(now), Some(next), "{}", now),
+        }
+        let mut prev = now - step;
+        while schedule.matches(prev) && now - prev < Duration::days(days) {
+            prev -= step;
+        }
+        match schedule.matches(prev) {
+            true => assert!(schedule.prev_unmatched(now).map_or(true, |x| x < prev), "{}", now),
+            false => assert_eq!(schedule.prev_unmatched(now), Some(prev), "{}", now),
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        let build = |days: Days, hours: Hours, seconds: Seconds| {
+            TimerConf::new(days, hours, Minuters::default_all(), seconds)
+        };
+        let conf = build(
+            Days::MonthDays(MonthDays::default_value(D29)),
+            Hours::default_value(H0),
+            Seconds::default_value(S0),
+        );
+        assert!(conf.validate().is_ok());
+        // 闰年才有的2月29日也算会触发
+        let leap = build(
+            Days::YearDays(YearDays::default_value(366).unwrap()),
+            Hours::default_all(),
+            Seconds::default_all(),
+        );
+        assert!(leap.validate().is_ok());
+
+        let err = build(
+            Days::MonthDays(MonthDays::default_all()),
+            Hours::default(),
+            Seconds::default(),
+        )
+        .validate()
+        .unwrap_err();
+        assert!(matches!(err, Error::Unsatisfiable { field: "hour" }));
+        assert_eq!(err.to_string(), "no hour is selected");
+        assert!(matches!(
+            build(
+                Days::MonthDays(MonthDays::default_all()),
+                Hours::default_all(),
+                Seconds::default()
+            )
+            .validate(),
+            Err(Error::Unsatisfiable { field: "second" })
+        ));
+        // 没有选中任何日期，或每项都选了值但组合起来没有日期，如1-7号里的第5周
+        let never = [
+            Days::MonthDays(MonthDays::default()),
+            Days::WeekDays(WeekDays::default()),
+            Days::MonthWeeks {
+                days: Box::new(Days::MonthDays(MonthDays::default_range(D1..=D7).unwrap())),
+                weeks: MonthWeeks::default_value(MonthWeek::Fifth),
+                start: None,
+            },
+        ];
+        for days in never {
+            assert!(matches!(
+                build(days, Hours::default_all(), Seconds::default_all()).validate(),
+                Err(Error::Unsatisfiable { field: "day" })
+            ));
+        }
+    }
     #[test]
     fn test_auto() -> anyhow::Result<()> {
-        // custom_utils::logger::logger_stdout_debug();
         let conf = configure_weekday(WeekDays::default_array(&[W1, W3, W5]))
             .conf_month_days(
                 MonthDays::default_range(D5..D10)?
@@ -420,12 +1147,11 @@ mod test {
         let mut start = datetime(2022, 7, 4, 20, 15, 0);
         let end = datetime(2033, 8, 15, 12, 30, 45);
 
-        // let datetimes = conf.datetimes(start.clone()..end)?;
-        let datetimes = conf.datetimes(start.clone()..end)?;
+        let datetimes = conf.datetimes(start..end)?;
         start = start.sub(Duration::seconds(1));
         let mut next = end;
         for datetime in datetimes {
-            next = conf.next_with_time(start.clone());
+            next = conf.next_with_time(start);
             assert_eq!(datetime, next, "{:?} - {:?}", start, next);
             start = datetime;
         }
@@ -439,12 +1165,11 @@ mod test {
 
         let mut start = datetime(2022, 7, 4, 20, 15, 0);
         let end = datetime(2033, 8, 15, 15, 30, 0);
-        // let datetimes = conf.datetimes(start.clone()..end.clone())?;
-        let datetimes = conf.datetimes(start.clone()..end.clone())?;
+        let datetimes = conf.datetimes(start..end)?;
         start = start.sub(Duration::seconds(1));
-        let mut next = start.clone();
+        let mut next = start;
         for datetime in datetimes {
-            next = conf.next_with_time(start.clone());
+            next = conf.next_with_time(start);
             assert_eq!(datetime, next, "{:?} - {:?}", start, next);
             start = datetime;
         }
@@ -459,7 +1184,6 @@ mod test {
     }
     #[test]
     fn test_auto_pre() -> anyhow::Result<()> {
-        // custom_utils::logger::logger_stdout_debug();
         let conf = configure_weekday(WeekDays::default_array(&[W1, W3, W5]))
             .conf_month_days(
                 MonthDays::default_range(D5..D10)?
@@ -473,8 +1197,7 @@ mod test {
         let start = datetime(2022, 7, 4, 22, 17, 10);
         let end = datetime(2022, 7, 5, 12, 30, 45);
 
-        // let datetimes = conf.datetimes(start.clone()..end)?;
-        let datetimes = conf.datetimes(start.clone()..end)?;
+        let datetimes = conf.datetimes(start..end)?;
         debug!("{:?}", datetimes);
         Ok(())
     }
@@ -498,11 +1221,9 @@ mod test {
             month_days2.to_vec(),
             vec![1, 3, 5, 6, 8, 10, 12, 13, 15, 17, 19, 20, 22, 24, 26, 27, 29, 31]
         );
-        // debug!("{:?}", month_days.to_vec());
     }
     #[test]
     fn test_datetimes() -> Result<()> {
-        // custom_utils::logger::logger_stdout_debug();
         let some_datetimes = [
             datetime(2020, 5, 15, 10, 30, 30),
             datetime(2020, 5, 15, 10, 30, 45),
@@ -521,8 +1242,6 @@ mod test {
             .build_with_minuter(Minuters::default_array(&[M15, M30, M45]))
             .build_with_second(Seconds::default_array(&[S15, S30, S45]));
         debug!("2020-5-15 10:30:17");
-        // let datetimes =
-        //     conf.datetimes(datetime(2020, 5, 15, 10, 30, 17)..=datetime(2020, 5, 15, 15, 30, 30))?;
         let datetimes =
             conf.datetimes(datetime(2020, 5, 15, 10, 30, 17)..=datetime(2020, 5, 15, 15, 30, 30))?;
 
@@ -533,7 +1252,6 @@ mod test {
 
     #[test]
     fn test() -> Result<()> {
-        // custom_utils::logger::logger_stdout_debug();
         let conf = configure_weekday(WeekDays::default_array(&[W5, W3]))
             .conf_month_days(MonthDays::default_array(&[D5, D15, D24]))
             .build_with_hours(Hours::default_array(&[H5, H10, H15]))
@@ -566,7 +1284,7 @@ mod test {
         };
         {
             let dist: DateTime = conf.next_with_time(dt0.into()).into();
-            let mut dt0_dist = dt0.clone();
+            let mut dt0_dist = dt0;
             dt0_dist.week_day = W2;
             dt0_dist.month_day = D24;
             dt0_dist.second = S15;
@@ -592,7 +1310,7 @@ mod test {
         };
         {
             let dist: DateTime = conf.next_with_time(dt0.into()).into();
-            let mut dt0_dist = dt0.clone();
+            let mut dt0_dist = dt0;
             dt0_dist.week_day = W3;
             dt0_dist.month_day = D4;
             dt0_dist.second = S15;
@@ -606,7 +1324,6 @@ mod test {
 
     #[test]
     fn test_year() -> Result<()> {
-        // custom_utils::logger::logger_stdout_debug();
         let conf = configure_monthday(MonthDays::default_value(D31))
             .build_with_hours(Hours::default_array(&[H12]))
             .build_with_minuter(Minuters::default_array(&[M30]))
@@ -621,7 +1338,7 @@ mod test {
         };
         {
             let dist: DateTime = conf.next_with_time(dt0.into()).into();
-            let mut dt0_dist = dist.clone();
+            let mut dt0_dist = dist;
             dt0_dist.second = S0;
             dt0_dist.minuter = M30;
             dt0_dist.hour = H12;
@@ -635,7 +1352,6 @@ mod test {
     }
     #[test]
     fn test_month() -> Result<()> {
-        // custom_utils::logger::logger_stdout_debug();
         let conf = configure_monthday(MonthDays::default_value(D31))
             .build_with_hours(Hours::default_array(&[H12]))
             .build_with_minuter(Minuters::default_array(&[M30]))
@@ -650,7 +1366,7 @@ mod test {
         };
         {
             let dist: DateTime = conf.next_with_time(dt0.into()).into();
-            let mut dt0_dist = dist.clone();
+            let mut dt0_dist = dist;
             dt0_dist.second = S0;
             dt0_dist.minuter = M30;
             dt0_dist.hour = H12;
@@ -663,12 +1379,364 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn test_prev() -> Result<()> {
+        let conf = configure_weekday(WeekDays::default_array(&[W1, W3, W5]))
+            .conf_month_days(MonthDays::default_range(D5..D10)?.add_range(D25..D30)?)
+            .build_with_hours(Hours::default_array(&[H5, H10, H15, H20]))
+            .build_with_minuter(Minuters::default_array(&[M15, M30, M45]))
+            .build_with_second(Seconds::default_array(&[S0, S30]));
+        let start = datetime(2022, 1, 20, 20, 15, 0);
+        let end = datetime(2022, 6, 15, 12, 30, 45);
+        let datetimes = conf.datetimes(start..=end)?;
+        let mut now = end;
+        for datetime in datetimes.iter().rev() {
+            let prev = conf.prev_with_time(now);
+            assert_eq!(*datetime, prev, "{:?}", now);
+            now = prev;
+        }
+        assert_eq!(conf.prev_with_time(datetime(2022, 3, 1, 5, 15, 0)), datetime(2022, 2, 28, 20, 45, 30));
+        Ok(())
+    }
+
+    #[test]
+    fn test_days_mode() -> Result<()> {
+        // 13号且是周五
+        let conf = configure_weekday(WeekDays::default_value(W5))
+            .conf_mode(DaysMode::And)
+            .conf_month_days(MonthDays::default_value(D13))
+            .build_with_hours(Hours::default_value(H0))
+            .build_with_minuter(Minuters::default_value(M0))
+            .build_with_second(Seconds::default_value(S0));
+        assert_eq!(
+            conf.datetimes(datetime(2022, 1, 1, 0, 0, 0)..datetime(2024, 1, 1, 0, 0, 0))?,
+            vec![
+                datetime(2022, 5, 13, 0, 0, 0),
+                datetime(2023, 1, 13, 0, 0, 0),
+                datetime(2023, 10, 13, 0, 0, 0),
+            ]
+        );
+        assert_eq!(
+            conf.prev_with_time(datetime(2023, 1, 13, 0, 0, 0)),
+            datetime(2022, 5, 13, 0, 0, 0)
+        );
+        // 1-7号里的周一；组合方式可以在之后设置
+        let builder = configure_monthday(MonthDays::default_range(D1..=D7)?)
+            .conf_week_days(WeekDays::default_value(W1));
+        let and = builder.conf_mode(DaysMode::And);
+        assert_eq!(and.days.month_days(2022, 8).to_vec(), vec![1]);
+        let or = and.conf_mode(DaysMode::Or);
+        assert_eq!(
+            or.days.month_days(2022, 8).to_vec(),
+            vec![1, 2, 3, 4, 5, 6, 7, 8, 15, 22, 29]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_days_mode_text() -> Result<()> {
+        for mode in [DaysMode::Or, DaysMode::And] {
+            assert_eq!(mode.to_string().parse::<DaysMode>()?, mode);
+        }
+        assert_eq!(DaysMode::And.to_string(), "and");
+        assert_eq!(" OR ".parse::<DaysMode>()?, DaysMode::Or);
+        assert!(matches!("xor".parse::<DaysMode>(), Err(Error::ParseText { .. })));
+        assert!("".parse::<DaysMode>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_month_weeks() -> Result<()> {
+        // 每月第1、3个周一，及最后一个周五
+        let conf = configure_weekday(WeekDays::default_value(W1))
+            .conf_month_weeks(MonthWeeks::default_array(&[MonthWeek::First, MonthWeek::Third]))
+            .build_with_hours(Hours::default_value(H9))
+            .build_with_minuter(Minuters::default_value(M0))
+            .build_with_second(Seconds::default_value(S0));
+        assert_eq!(
+            conf.datetimes(datetime(2022, 10, 1, 0, 0, 0)..datetime(2022, 11, 30, 0, 0, 0))?,
+            vec![
+                datetime(2022, 10, 3, 9, 0, 0),
+                datetime(2022, 10, 17, 9, 0, 0),
+                datetime(2022, 11, 7, 9, 0, 0),
+                datetime(2022, 11, 21, 9, 0, 0),
+            ]
+        );
+        let conf = configure_weekday(WeekDays::default_value(W5))
+            .conf_month_weeks(MonthWeeks::default_value(MonthWeek::Last))
+            .build_with_hours(Hours::default_value(H9))
+            .build_with_minuter(Minuters::default_value(M0))
+            .build_with_second(Seconds::default_value(S0));
+        assert_eq!(
+            conf.next_with_time(datetime(2022, 10, 1, 0, 0, 0)),
+            datetime(2022, 10, 28, 9, 0, 0)
+        );
+        assert_eq!(
+            conf.prev_with_time(datetime(2022, 10, 28, 9, 0, 0)),
+            datetime(2022, 9, 30, 9, 0, 0)
+        );
+        // 2月只有28天时没有第5周
+        assert!(MonthWeeks::default_value(MonthWeek::Fifth)
+            .to_month_days(2022, 2, None)
+            .is_zero());
+        Ok(())
+    }
+
+    #[test]
+    fn test_calendar_weeks() -> Result<()> {
+        // 2024-05-01是周三，5月共31天
+        let build = |start: WeekStart, weeks: &[MonthWeek]| {
+            configure_monthday(MonthDays::default_all())
+                .conf_week_start(start)
+                .conf_calendar_weeks(MonthWeeks::default_array(weeks))
+                .build_with_hours(Hours::default_value(H0))
+                .build_with_minuter(Minuters::default_value(M0))
+                .build_with_second(Seconds::default_value(S0))
+        };
+        let days = |conf: &TimerConf| conf.days.month_days(2024, 5).to_vec();
+        // 周一开始：1-5号为第1周，6-12号为第2周，27-31号为最后一周
+        let weeks = [MonthWeek::First, MonthWeek::Second, MonthWeek::Last];
+        let conf = build(WeekStart::Monday, &weeks);
+        assert_eq!(
+            days(&conf),
+            vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 27, 28, 29, 30, 31]
+        );
+        // 周日开始：1-4号为第1周，5-11号为第2周，26-31号为最后一周
+        let conf = build(WeekStart::Sunday, &weeks);
+        assert_eq!(
+            days(&conf),
+            vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 26, 27, 28, 29, 30, 31]
+        );
+        // 第5周只剩下当月的天数
+        let conf = build(WeekStart::Monday, &[MonthWeek::Fifth]);
+        assert_eq!(days(&conf), vec![27, 28, 29, 30, 31]);
+        // 2024-06-01是周六，周日开始时有6行：第5周为23-29号，最后一周为30号
+        let weeks = [MonthWeek::First, MonthWeek::Fifth, MonthWeek::Last];
+        let conf = build(WeekStart::Sunday, &weeks);
+        assert_eq!(
+            conf.days.month_days(2024, 6).to_vec(),
+            vec![1, 23, 24, 25, 26, 27, 28, 29, 30]
+        );
+        // 与按日期划分的区别：第1个周一是5月6日，而日历的第1行没有周一
+        let conf = configure_weekday(WeekDays::default_value(W1))
+            .conf_calendar_weeks(MonthWeeks::default_value(MonthWeek::First))
+            .build_with_hours(Hours::default_value(H0))
+            .build_with_minuter(Minuters::default_value(M0))
+            .build_with_second(Seconds::default_value(S0));
+        assert_eq!(
+            conf.next_with_time(datetime(2024, 5, 1, 0, 0, 0)),
+            datetime(2024, 7, 1, 0, 0, 0)
+        );
+        // 之后再设置每周的第一天也生效
+        let conf = configure_monthday(MonthDays::default_all())
+            .conf_calendar_weeks(MonthWeeks::default_value(MonthWeek::First))
+            .conf_week_start(WeekStart::Sunday)
+            .build_with_hours(Hours::default_value(H0))
+            .build_with_minuter(Minuters::default_value(M0))
+            .build_with_second(Seconds::default_value(S0));
+        assert_eq!(days(&conf), vec![1, 2, 3, 4]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_iso_weeks() -> Result<()> {
+        // 单周的周五：2020-12-25为第52周；2021-01-01属于2020年第53周，紧接着2021年第1周
+        let conf = configure_weekday(WeekDays::default_value(W5))
+            .conf_iso_weeks(IsoWeeks::every(2)?)
+            .build_with_hours(Hours::default_value(H18))
+            .build_with_minuter(Minuters::default_value(M0))
+            .build_with_second(Seconds::default_value(S0));
+        assert_eq!(
+            conf.datetimes(datetime(2020, 12, 20, 0, 0, 0)..datetime(2021, 2, 1, 0, 0, 0))?,
+            vec![
+                datetime(2021, 1, 1, 18, 0, 0),
+                datetime(2021, 1, 8, 18, 0, 0),
+                datetime(2021, 1, 22, 18, 0, 0),
+            ]
+        );
+        assert_eq!(IsoWeeks::every(26)?.to_vec(), vec![1, 27, 53]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_week_start() -> Result<()> {
+        // cron的编号：0、7为周日
+        let week_days = WeekDays::default_numbers(&[0, 6], WeekStart::Sunday)?;
+        assert_eq!(week_days.to_vec(), vec![6, 7]);
+        assert_eq!(week_days.to_vec_by(WeekStart::Sunday), vec![0, 6]);
+        assert_eq!(week_days.to_vec_by(WeekStart::Monday), vec![6, 7]);
+        assert_eq!(WeekStart::Sunday.parse(7)?, W7);
+        assert!(WeekStart::Monday.parse(0).is_err());
+        assert!(WeekStart::Sunday.parse(8).is_err());
+        // 文本形式
+        assert_eq!(week_days.to_string_by(WeekStart::Sunday), "0,6");
+        assert_eq!(week_days.to_string_by(WeekStart::Monday), "6,7");
+        assert_eq!(WeekDays::parse_by("0,6", WeekStart::Sunday)?, week_days);
+        assert_eq!(
+            WeekDays::parse_by("1-5", WeekStart::Sunday)?,
+            WeekDays::default_range(W1..=W5)?
+        );
+        assert_eq!(WeekDays::parse_by("7", WeekStart::Monday)?, WeekDays::default_value(W7));
+        assert!(WeekDays::parse_by("0", WeekStart::Monday).is_err());
+        assert!(WeekDays::parse_by("5-1", WeekStart::Sunday).is_err());
+        assert!(WeekDays::parse_by("mon", WeekStart::Sunday).is_err());
+
+        // 周一开始时与ISO 8601一致
+        let mut date = NaiveDate::from_ymd(2020, 12, 1);
+        while date.year() < 2022 {
+            assert_eq!(WeekStart::Monday.week_number(date), date.iso_week().week());
+            date = date.succ();
+        }
+        // 2023-01-01是周日：按周一开始属于2022年第52周，按周日开始为2023年第1周
+        let conf = configure_weekday(WeekDays::default_value(W7))
+            .conf_iso_weeks(IsoWeeks::every(2)?)
+            .build_with_hours(Hours::default_value(H0))
+            .build_with_minuter(Minuters::default_value(M0))
+            .build_with_second(Seconds::default_value(S0));
+        assert_eq!(
+            conf.next_with_time(datetime(2022, 12, 30, 0, 0, 0)),
+            datetime(2023, 1, 8, 0, 0, 0)
+        );
+        let conf = configure_weekday(WeekDays::default_value(W7))
+            .conf_iso_weeks(IsoWeeks::every(2)?)
+            .conf_week_start(WeekStart::Sunday)
+            .build_with_hours(Hours::default_value(H0))
+            .build_with_minuter(Minuters::default_value(M0))
+            .build_with_second(Seconds::default_value(S0));
+        assert_eq!(
+            conf.datetimes(datetime(2022, 12, 30, 0, 0, 0)..datetime(2023, 1, 31, 0, 0, 0))?,
+            vec![
+                datetime(2023, 1, 1, 0, 0, 0),
+                datetime(2023, 1, 15, 0, 0, 0),
+                datetime(2023, 1, 29, 0, 0, 0),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_year_days() -> Result<()> {
+        let build = |year_days: YearDays| {
+            configure_yearday(year_days)
+                .build_with_hours(Hours::default_value(H0))
+                .build_with_minuter(Minuters::default_value(M0))
+                .build_with_second(Seconds::default_value(S0))
+        };
+        // 第60天：平年为3月1日，闰年为2月29日
+        let conf = build(YearDays::default_value(60)?.add_last());
+        assert_eq!(
+            conf.datetimes(datetime(2023, 1, 1, 0, 0, 0)..datetime(2025, 1, 1, 0, 0, 0))?,
+            vec![
+                datetime(2023, 3, 1, 0, 0, 0),
+                datetime(2023, 12, 31, 0, 0, 0),
+                datetime(2024, 2, 29, 0, 0, 0),
+                datetime(2024, 12, 31, 0, 0, 0),
+            ]
+        );
+        // 第366天只在闰年出现
+        let conf = build(YearDays::default_value(366)?);
+        assert_eq!(
+            conf.next_with_time(datetime(2021, 1, 1, 0, 0, 0)),
+            datetime(2024, 12, 31, 0, 0, 0)
+        );
+        assert_eq!(
+            conf.prev_with_time(datetime(2024, 12, 31, 0, 0, 0)),
+            datetime(2020, 12, 31, 0, 0, 0)
+        );
+        assert_eq!(YearDays::default_range(364..)?.to_vec(), vec![364, 365, 366]);
+        assert!(YearDays::default_range(0..3).is_err());
+        assert!(YearDays::default_array(&[1, 367]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_year_days_bounds() -> Result<()> {
+        // 超出1-366的值返回错误，而不是panic
+        assert!(matches!(
+            YearDays::default_value(0),
+            Err(Error::OutOfRange { value: 0, .. })
+        ));
+        assert!(matches!(
+            YearDays::default_value(367),
+            Err(Error::OutOfRange { value: 367, .. })
+        ));
+        assert!(YearDays::default_range(..=0).is_err());
+        assert!(YearDays::default_range(0..=366).is_err());
+        // 排除的端点不会溢出
+        assert_eq!(
+            YearDays::default_range((Bound::Excluded(0), Bound::Included(2)))?.to_vec(),
+            vec![1, 2]
+        );
+        assert!(matches!(
+            YearDays::default_range((Bound::Included(1), Bound::Excluded(0))),
+            Err(Error::EmptyRange { .. })
+        ));
+        assert!(matches!(
+            YearDays::default_range((Bound::Excluded(366), Bound::Unbounded)),
+            Err(Error::EmptyRange { .. })
+        ));
+        assert!(YearDays::default_range((Bound::Excluded(u16::MAX), Bound::Unbounded)).is_err());
+        let days = YearDays::default_value(1)?;
+        assert!(!days.contain(0));
+        assert!(!days.contain(367));
+        assert!(!days.contain(u16::MAX));
+        Ok(())
+    }
+
+    #[test]
+    fn test_matches() -> Result<()> {
+        use chrono::{FixedOffset, TimeZone};
+        // 每月13号且是周五，9:30:00
+        let conf = configure_weekday(WeekDays::default_value(W5))
+            .conf_month_days(MonthDays::default_value(D13))
+            .conf_mode(DaysMode::And)
+            .build_with_hours(Hours::default_value(H9))
+            .build_with_minuter(Minuters::default_value(M30))
+            .build_with_second(Seconds::default_value(S0));
+        assert!(conf.contains_date(NaiveDate::from_ymd(2023, 10, 13)));
+        assert!(!conf.contains_date(NaiveDate::from_ymd(2023, 11, 13)));
+        assert!(!conf.contains_date(NaiveDate::from_ymd(2023, 10, 20)));
+        assert!(conf.matches(datetime(2023, 10, 13, 9, 30, 0)));
+        assert!(!conf.matches(datetime(2023, 10, 13, 9, 30, 1)));
+        assert!(!conf.matches(datetime(2023, 10, 13, 10, 30, 0)));
+        assert!(!conf.matches(datetime(2023, 11, 13, 9, 30, 0)));
+        assert!(Schedule::matches(&conf, datetime(2023, 10, 13, 9, 30, 0)));
+
+        // 东八区2023-10-13 09:30即UTC 2023-10-13 01:30
+        let cst = FixedOffset::east(8 * 3600);
+        assert!(conf.matches_tz(&cst.ymd(2023, 10, 13).and_hms(9, 30, 0)));
+        let utc = chrono::Utc.ymd(2023, 10, 13).and_hms(9, 30, 0);
+        assert!(!conf.matches_tz(&utc.with_timezone(&cst)));
+        let local = cst.ymd(2023, 10, 13).and_hms(9, 30, 0);
+        assert!(!conf.matches_tz(&local.with_timezone(&chrono::Utc)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_try_from() -> Result<()> {
+        assert_eq!(MonthDay::try_from(31u8)?, D31);
+        assert_eq!(Hour::try_from(23u32)?, H23);
+        assert_eq!(Second::try_from(0u64)?, S0);
+        assert!(MonthDay::try_from(0u8).is_err());
+        assert!(WeekDay::try_from(8u32).is_err());
+        assert!(Minuter::try_from(60u64).is_err());
+        assert!(IsoWeek::try_from(u64::MAX).is_err());
+
+        assert_eq!(MonthDays::default_data(&[1, 15])?.to_vec(), vec![1, 15]);
+        assert!(MonthDays::default_data(&[1, 32]).is_err());
+        assert!(Hours::default_value(H1).add_data(&[24]).is_err());
+        // 不会panic：结束端点不包含最小值，或起点不包含最大值
+        assert!(MonthDays::default_range(..D1).is_err());
+        assert!(MonthDays::default_range((Bound::Excluded(D31), Bound::Unbounded)).is_err());
+        Ok(())
+    }
+
     fn compare(conf: &TimerConf, times: &[NaiveDateTime]) {
         let len = times.len() - 1;
         let mut index = 0;
         loop {
             assert_eq!(
-                conf.next_with_time(times[index].clone()),
+                conf.next_with_time(times[index]),
                 times[index + 1].clone()
             );
             index += 1;
@@ -678,20 +1746,83 @@ mod test {
         }
     }
     #[test]
-    fn test_every() {
+    fn test_every() -> Result<()> {
         use Minuter::*;
-        let minuters = Minuters::every(11);
+        let minuters = Minuters::every(11)?;
         assert_eq!(minuters, Minuters::default_array(&[M0, M11, M22, M33, M44, M55]));
-        let minuters = Minuters::every(0);
-        assert_eq!(minuters, Minuters::_default());
-        let minuters = Minuters::every(30);
+        let minuters = Minuters::every(30)?;
         assert_eq!(minuters, Minuters::default_array(&[M0, M30]));
-        let minuters = Minuters::every(31);
+        let minuters = Minuters::every(31)?;
         assert_eq!(minuters, Minuters::default_array(&[M0, M31]));
-        let minuters = Minuters::every(60);
+        let minuters = Minuters::every(60)?;
         assert_eq!(minuters, Minuters::default_array(&[M0]));
 
-        let minuters = Minuters::every(500);
+        let minuters = Minuters::every(500)?;
         assert_eq!(minuters, Minuters::default_array(&[M0]));
+        assert!(Minuters::every(u64::MAX)?.contain(M0));
+
+        assert_eq!(Seconds::every(15)?.to_vec(), vec![0, 15, 30, 45]);
+        assert_eq!(Hours::every(6)?.to_vec(), vec![0, 6, 12, 18]);
+        assert_eq!(MonthDays::every(10)?.to_vec(), vec![1, 11, 21, 31]);
+        assert_eq!(WeekDays::every(2)?.to_vec(), vec![1, 3, 5, 7]);
+        Ok(())
+    }
+    #[test]
+    fn test_every_zero_step() {
+        // 步长为0时every与add_range_step一样返回错误，而不是返回空配置
+        assert!(matches!(
+            Minuters::every(0),
+            Err(Error::InvalidStep {
+                field: "minuter",
+                step: 0
+            })
+        ));
+        assert!(matches!(
+            IsoWeeks::every(0),
+            Err(Error::InvalidStep {
+                field: "iso week",
+                step: 0
+            })
+        ));
+        assert!(matches!(
+            Minuters::default_range_step(.., 0),
+            Err(Error::InvalidStep { step: 0, .. })
+        ));
+        // 其他步长与不限区间的default_range_step相同
+        for step in [1, 7, 59, 60, u64::MAX] {
+            assert_eq!(
+                Minuters::every(step).unwrap(),
+                Minuters::default_range_step(.., step).unwrap()
+            );
+        }
+        assert_eq!(Hours::every(1).unwrap(), Hours::default_all());
+    }
+    #[test]
+    fn test_range_step() -> Result<()> {
+        use Minuter::*;
+        // 3-58分，每5分钟
+        let minuters = Minuters::default_range_step(M3..=M58, 5)?;
+        assert_eq!(minuters._min_val(), Some(3));
+        assert_eq!(minuters._max_val(), Some(58));
+        assert_eq!(minuters.to_vec().len(), 12);
+        // 8-20点，每3小时
+        assert_eq!(
+            Hours::default_range_step(H8..=H20, 3)?.to_vec(),
+            vec![8, 11, 14, 17, 20]
+        );
+        assert_eq!(
+            Hours::default_value(H0).add_range_step(H13.., 5)?.to_vec(),
+            vec![0, 13, 18, 23]
+        );
+        assert_eq!(
+            Seconds::default_range_step(.., u64::MAX)?.to_vec(),
+            vec![0]
+        );
+        assert!(matches!(
+            Hours::default_range_step(H8..=H20, 0),
+            Err(Error::InvalidStep { field: "hour", step: 0 })
+        ));
+        assert!(MonthDays::default_range_step(D20..D10, 2).is_err());
+        Ok(())
     }
 }
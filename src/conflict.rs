@@ -0,0 +1,278 @@
+use crate::compiled::CompiledTimer;
+use crate::conf::TimerConf;
+use crate::error::Result;
+use crate::schedule::{span, Schedule};
+use crate::traits::ConfigOperator;
+use chrono::{Duration, NaiveDateTime};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+use std::ops::{RangeBounds, Sub};
+
+/// 两个定时器的一次冲突：在同一秒、或相隔不超过容差的时间内触发
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct Collision {
+    /// 先触发的定时器的序号
+    pub first: usize,
+    /// 先触发的时间点
+    pub first_time: NaiveDateTime,
+    /// 后触发的定时器的序号
+    pub second: usize,
+    /// 后触发的时间点
+    pub second_time: NaiveDateTime,
+}
+
+/// 冲突检测：找出多个定时器在同一秒、或相隔不超过容差的时间内触发的情况，用于错开负载。
+///
+/// 容差为0时，先按时、分、秒逐项取交集排除不可能冲突的定时器对，再用取交集后的预编译定时器直接查找；
+/// 否则按时间顺序合并各定时器的触发时间点逐个比较
+#[derive(Debug, Clone)]
+pub struct ConflictDetector {
+    confs: Vec<TimerConf>,
+    timers: Vec<CompiledTimer>,
+    tolerance: Duration,
+}
+
+impl ConflictDetector {
+    pub fn new(confs: &[TimerConf]) -> Self {
+        Self {
+            confs: confs.to_vec(),
+            timers: confs.iter().map(CompiledTimer::new).collect(),
+            tolerance: Duration::zero(),
+        }
+    }
+    /// 相隔不超过tolerance触发也算冲突，默认为0，即只检测同一秒的触发
+    pub fn tolerance(mut self, tolerance: Duration) -> Self {
+        self.tolerance = tolerance.max(Duration::zero());
+        self
+    }
+    /// 可能在同一秒触发的定时器对：时、分、秒逐项取交集均不为空
+    pub fn coincident_pairs(&self) -> Vec<(usize, usize)> {
+        let mut pairs = Vec::new();
+        for (first, conf) in self.confs.iter().enumerate() {
+            for (second, other) in self.confs.iter().enumerate().skip(first + 1) {
+                if may_coincide(conf, other) {
+                    pairs.push((first, second));
+                }
+            }
+        }
+        pairs
+    }
+    /// now之后（不包含）、until之前（包含）的第一次冲突
+    pub fn first_collision(&self, now: NaiveDateTime, until: NaiveDateTime) -> Option<Collision> {
+        if self.tolerance.is_zero() {
+            return self
+                .coincident_pairs()
+                .into_iter()
+                .filter_map(|(first, second)| {
+                    let time = self.timers[first]
+                        .intersection(&self.timers[second])
+                        .next_with_time(now)
+                        .filter(|time| *time <= until)?;
+                    Some(Collision {
+                        first,
+                        first_time: time,
+                        second,
+                        second_time: time,
+                    })
+                })
+                .min_by_key(|x| (x.first_time, x.first, x.second));
+        }
+        let mut first = None;
+        self.sweep(now, until, |collision| {
+            first = Some(collision);
+            false
+        });
+        first
+    }
+    /// 给定的日期时间范围内的所有冲突，按时间先后排列
+    pub fn collisions(&self, range: impl RangeBounds<NaiveDateTime>) -> Result<Vec<Collision>> {
+        let (start, end) = span(&range, (None, None))?;
+        let mut collisions = Vec::new();
+        self.sweep(start, end, |collision| {
+            collisions.push(collision);
+            true
+        });
+        Ok(collisions)
+    }
+
+    /// 按时间顺序合并(start, end]内各定时器的触发时间点，与容差内的前几个时间点比较。
+    /// f返回false时停止
+    fn sweep(
+        &self,
+        start: NaiveDateTime,
+        end: NaiveDateTime,
+        mut f: impl FnMut(Collision) -> bool,
+    ) {
+        // 容差为0时，只需合并可能在同一秒触发的定时器
+        let candidates: Vec<usize> = if self.tolerance.is_zero() {
+            let mut candidates: Vec<usize> = self
+                .coincident_pairs()
+                .into_iter()
+                .flat_map(|(first, second)| [first, second])
+                .collect();
+            candidates.sort_unstable();
+            candidates.dedup();
+            candidates
+        } else {
+            (0..self.timers.len()).collect()
+        };
+        let mut heap = BinaryHeap::new();
+        for index in candidates {
+            if let Some(next) = self.timers[index].next_with_time(start) {
+                heap.push(Reverse((next, index)));
+            }
+        }
+        // 容差内已经触发的时间点
+        let mut recent: VecDeque<(NaiveDateTime, usize)> = VecDeque::new();
+        while let Some(Reverse((time, index))) = heap.pop() {
+            if time > end {
+                break;
+            }
+            while recent
+                .front()
+                .is_some_and(|(last, _)| time.sub(*last) > self.tolerance)
+            {
+                recent.pop_front();
+            }
+            for (last, other) in recent.iter() {
+                if *other != index {
+                    let collision = Collision {
+                        first: *other,
+                        first_time: *last,
+                        second: index,
+                        second_time: time,
+                    };
+                    if !f(collision) {
+                        return;
+                    }
+                }
+            }
+            recent.push_back((time, index));
+            if let Some(next) = self.timers[index].next_with_time(time) {
+                heap.push(Reverse((next, index)));
+            }
+        }
+    }
+}
+
+/// 时、分、秒逐项取交集均不为空
+fn may_coincide(conf: &TimerConf, other: &TimerConf) -> bool {
+    !conf.hours.intersection(&other.hours).is_zero()
+        && !conf.minuters.intersection(&other.minuters).is_zero()
+        && !conf.seconds.intersection(&other.seconds).is_zero()
+}
+
+impl TimerConf {
+    /// now之后（不包含）第一个与other在同一秒触发的时间点，400年内都没有则返回None
+    pub fn first_coincidence(
+        &self,
+        other: &TimerConf,
+        now: NaiveDateTime,
+    ) -> Option<NaiveDateTime> {
+        if !may_coincide(self, other) {
+            return None;
+        }
+        self.compile()
+            .intersection(&other.compile())
+            .next_with_time(now)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::conf::test::datetime;
+    use crate::*;
+    use chrono::Duration;
+
+    fn conf(days: impl Into<builder::DayConfBuilder>, hour: Hour, second: Second) -> TimerConf {
+        days.into()
+            .build_with_hours(Hours::default_value(hour))
+            .build_with_minuter(Minuters::default_value(M0))
+            .build_with_second(Seconds::default_value(second))
+    }
+
+    #[test]
+    fn test_conflict() -> anyhow::Result<()> {
+        let confs = [
+            // 每天9:00:00
+            conf(MonthDays::default_all(), H9, S0),
+            // 每周一9:00:00
+            conf(WeekDays::default_value(W1), H9, S0),
+            // 每天9:00:30
+            conf(MonthDays::default_all(), H9, S30),
+            // 每周二10:00:00
+            conf(WeekDays::default_value(W2), H10, S0),
+        ];
+        // 2024-01-01是周一
+        let now = datetime(2024, 1, 2, 0, 0, 0);
+        assert_eq!(
+            confs[0].first_coincidence(&confs[1], now),
+            Some(datetime(2024, 1, 8, 9, 0, 0))
+        );
+        assert_eq!(confs[0].first_coincidence(&confs[2], now), None);
+        assert_eq!(confs[1].first_coincidence(&confs[3], now), None);
+
+        let detector = ConflictDetector::new(&confs);
+        assert_eq!(detector.coincident_pairs(), vec![(0, 1)]);
+        let collision = Collision {
+            first: 0,
+            first_time: datetime(2024, 1, 8, 9, 0, 0),
+            second: 1,
+            second_time: datetime(2024, 1, 8, 9, 0, 0),
+        };
+        let until = datetime(2024, 2, 1, 0, 0, 0);
+        assert_eq!(detector.first_collision(now, until), Some(collision));
+        assert_eq!(
+            detector.first_collision(now, datetime(2024, 1, 8, 8, 59, 59)),
+            None
+        );
+        let collisions = detector.collisions(now..until)?;
+        assert_eq!(collisions.len(), 4);
+        assert!(collisions.iter().all(|x| x.first == 0 && x.second == 1));
+
+        // 相隔30秒内也算冲突
+        let detector = detector.tolerance(Duration::seconds(30));
+        assert_eq!(
+            detector.first_collision(now, until),
+            Some(Collision {
+                first: 0,
+                first_time: datetime(2024, 1, 2, 9, 0, 0),
+                second: 2,
+                second_time: datetime(2024, 1, 2, 9, 0, 30),
+            })
+        );
+        let collisions = detector.collisions(now..datetime(2024, 1, 9, 0, 0, 0))?;
+        // 每天0与2冲突，周一还有0与1、1与2冲突
+        assert_eq!(collisions.len(), 7 + 2);
+        assert!(collisions.iter().all(|x| x.first_time <= x.second_time));
+        assert!(!collisions.iter().any(|x| x.first == 3 || x.second == 3));
+        Ok(())
+    }
+
+    #[test]
+    fn test_coincidence_by_days() -> anyhow::Result<()> {
+        let now = datetime(2024, 1, 1, 0, 0, 0);
+        // 时分秒相同，日期永远不重合
+        let monday = conf(WeekDays::default_value(W1), H9, S0);
+        let tuesday = conf(WeekDays::default_value(W2), H9, S0);
+        assert_eq!(monday.first_coincidence(&tuesday, now), None);
+        assert!(ConflictDetector::new(&[monday, tuesday])
+            .first_collision(now, datetime(2400, 1, 1, 0, 0, 0))
+            .is_none());
+        // 周六与工作日只在调休上班的周六重合
+        let workday = conf(configure_workday(ChinaCalendar::bundled()), H9, S0);
+        let saturday = conf(WeekDays::default_value(W6), H9, S0);
+        assert_eq!(
+            workday.first_coincidence(&saturday, now),
+            Some(datetime(2024, 5, 11, 9, 0, 0))
+        );
+        // 春节与每月31日：下一次春节在1月31日是2033年
+        let spring = conf(LunarDays::default_value(L1, D1), H9, S0);
+        let last = conf(MonthDays::default_value(D31), H9, S0);
+        assert_eq!(
+            spring.first_coincidence(&last, now),
+            Some(datetime(2033, 1, 31, 9, 0, 0))
+        );
+        Ok(())
+    }
+}
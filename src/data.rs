@@ -1,7 +1,9 @@
 use crate::traits::{AsBizData, FromData};
 use crate::TryFromData;
-use anyhow::{bail, Result};
-use chrono::{Datelike, Local, NaiveDate, NaiveDateTime, NaiveTime, Timelike, Weekday as CWeekday};
+use crate::error::{Error, Result};
+use chrono::{
+    Datelike, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, Timelike, Weekday as CWeekday,
+};
 
 // use time::{OffsetDateTime, Weekday};
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
@@ -14,7 +16,7 @@ pub(crate) struct DateTime {
     pub(crate) second: Second,
 }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 #[repr(u64)]
 pub enum WeekDay {
     W1 = 1,
@@ -26,7 +28,7 @@ pub enum WeekDay {
     W7,
 }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 #[repr(u64)]
 pub enum MonthDay {
     D1 = 1,
@@ -62,7 +64,205 @@ pub enum MonthDay {
     D31,
 }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+/// 农历月份：正月为1，腊月为12
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[repr(u64)]
+pub enum LunarMonth {
+    L1 = 1,
+    L2,
+    L3,
+    L4,
+    L5,
+    L6,
+    L7,
+    L8,
+    L9,
+    L10,
+    L11,
+    L12,
+}
+
+/// 二十四节气，按公历年内的先后顺序：小寒为1，冬至为24
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[repr(u64)]
+pub enum SolarTerm {
+    /// 小寒
+    XiaoHan = 1,
+    /// 大寒
+    DaHan,
+    /// 立春
+    LiChun,
+    /// 雨水
+    YuShui,
+    /// 惊蛰
+    JingZhe,
+    /// 春分
+    ChunFen,
+    /// 清明
+    QingMing,
+    /// 谷雨
+    GuYu,
+    /// 立夏
+    LiXia,
+    /// 小满
+    XiaoMan,
+    /// 芒种
+    MangZhong,
+    /// 夏至
+    XiaZhi,
+    /// 小暑
+    XiaoShu,
+    /// 大暑
+    DaShu,
+    /// 立秋
+    LiQiu,
+    /// 处暑
+    ChuShu,
+    /// 白露
+    BaiLu,
+    /// 秋分
+    QiuFen,
+    /// 寒露
+    HanLu,
+    /// 霜降
+    ShuangJiang,
+    /// 立冬
+    LiDong,
+    /// 小雪
+    XiaoXue,
+    /// 大雪
+    DaXue,
+    /// 冬至
+    DongZhi,
+}
+
+/// 每周的第一天：决定星期的编号，及周数的计算
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Default)]
+pub enum WeekStart {
+    /// 周一为第一天，周一到周日编号为1-7（ISO 8601）
+    #[default]
+    Monday,
+    /// 周日为第一天，周日到周六编号为0-6，7也表示周日（cron）
+    Sunday,
+}
+
+impl WeekStart {
+    /// 每周的第一天
+    pub fn first_day(self) -> WeekDay {
+        match self {
+            WeekStart::Monday => WeekDay::W1,
+            WeekStart::Sunday => WeekDay::W7,
+        }
+    }
+    /// 按先后顺序的一周7天
+    pub fn days(self) -> [WeekDay; 7] {
+        use WeekDay::*;
+        match self {
+            WeekStart::Monday => [W1, W2, W3, W4, W5, W6, W7],
+            WeekStart::Sunday => [W7, W1, W2, W3, W4, W5, W6],
+        }
+    }
+    /// 星期的编号
+    pub fn number(self, day: WeekDay) -> u64 {
+        match (self, day) {
+            (WeekStart::Sunday, WeekDay::W7) => 0,
+            _ => day.as_data(),
+        }
+    }
+    /// 按编号解析星期
+    pub fn parse(self, number: u64) -> Result<WeekDay> {
+        match (self, number) {
+            (WeekStart::Sunday, 0) => Ok(WeekDay::W7),
+            (WeekStart::Sunday, 1..=7) | (WeekStart::Monday, 1..=7) => Ok(WeekDay::from_data(number)),
+            (WeekStart::Sunday, _) => Err(Error::out_of_range("week day", number, 0, 7)),
+            (WeekStart::Monday, _) => Err(Error::out_of_range("week day", number, 1, 7)),
+        }
+    }
+    /// 给定日期所在的周数：第1周为包含当年1月4日的那周，周一开始时即ISO 8601的周数
+    pub fn week_number(self, date: NaiveDate) -> u32 {
+        let offset = match self {
+            WeekStart::Monday => date.weekday().num_days_from_monday(),
+            WeekStart::Sunday => date.weekday().num_days_from_sunday(),
+        };
+        // 每周的第4天所在的年份即为该周所属的年份
+        let middle = date - Duration::days(offset as i64) + Duration::days(3);
+        middle.ordinal0() / 7 + 1
+    }
+}
+
+/// 月内的第几周：按日期划分，1-7号为第1周，8-14号为第2周……；Last为当月最后7天。
+/// 如第1周的周一即当月第1个周一，与每周的第一天无关
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[repr(u64)]
+pub enum MonthWeek {
+    First = 1,
+    Second,
+    Third,
+    Fourth,
+    Fifth,
+    Last,
+}
+
+/// ISO 8601的周数，每年第1周为包含该年第一个星期四的那周
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[repr(u64)]
+pub enum IsoWeek {
+    Wk1 = 1,
+    Wk2,
+    Wk3,
+    Wk4,
+    Wk5,
+    Wk6,
+    Wk7,
+    Wk8,
+    Wk9,
+    Wk10,
+    Wk11,
+    Wk12,
+    Wk13,
+    Wk14,
+    Wk15,
+    Wk16,
+    Wk17,
+    Wk18,
+    Wk19,
+    Wk20,
+    Wk21,
+    Wk22,
+    Wk23,
+    Wk24,
+    Wk25,
+    Wk26,
+    Wk27,
+    Wk28,
+    Wk29,
+    Wk30,
+    Wk31,
+    Wk32,
+    Wk33,
+    Wk34,
+    Wk35,
+    Wk36,
+    Wk37,
+    Wk38,
+    Wk39,
+    Wk40,
+    Wk41,
+    Wk42,
+    Wk43,
+    Wk44,
+    Wk45,
+    Wk46,
+    Wk47,
+    Wk48,
+    Wk49,
+    Wk50,
+    Wk51,
+    Wk52,
+    Wk53,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 #[repr(u64)]
 pub enum Hour {
     H0 = 0,
@@ -91,7 +291,7 @@ pub enum Hour {
     H23,
 }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 #[repr(u64)]
 pub enum Minuter {
     M0 = 0,
@@ -156,7 +356,7 @@ pub enum Minuter {
     M59,
 }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 #[repr(u64)]
 pub enum Second {
     S0 = 0,
@@ -272,6 +472,26 @@ impl AsBizData<u64> for MonthDay {
         self as u64
     }
 }
+impl AsBizData<u64> for LunarMonth {
+    fn as_data(self) -> u64 {
+        self as u64
+    }
+}
+impl AsBizData<u64> for SolarTerm {
+    fn as_data(self) -> u64 {
+        self as u64
+    }
+}
+impl AsBizData<u64> for MonthWeek {
+    fn as_data(self) -> u64 {
+        self as u64
+    }
+}
+impl AsBizData<u64> for IsoWeek {
+    fn as_data(self) -> u64 {
+        self as u64
+    }
+}
 impl AsBizData<u64> for Hour {
     fn as_data(self) -> u64 {
         self as u64
@@ -363,6 +583,136 @@ impl FromData<u64> for WeekDay {
     }
 }
 
+impl FromData<u64> for LunarMonth {
+    fn from_data(val: u64) -> Self {
+        assert!(val < 13 && val != 0);
+        match val {
+            1 => Self::L1,
+            2 => Self::L2,
+            3 => Self::L3,
+            4 => Self::L4,
+            5 => Self::L5,
+            6 => Self::L6,
+            7 => Self::L7,
+            8 => Self::L8,
+            9 => Self::L9,
+            10 => Self::L10,
+            11 => Self::L11,
+            12 => Self::L12,
+            _ => unreachable!("bug!"),
+        }
+    }
+}
+
+impl FromData<u64> for SolarTerm {
+    fn from_data(val: u64) -> Self {
+        assert!(val < 25 && val != 0);
+        match val {
+            1 => Self::XiaoHan,
+            2 => Self::DaHan,
+            3 => Self::LiChun,
+            4 => Self::YuShui,
+            5 => Self::JingZhe,
+            6 => Self::ChunFen,
+            7 => Self::QingMing,
+            8 => Self::GuYu,
+            9 => Self::LiXia,
+            10 => Self::XiaoMan,
+            11 => Self::MangZhong,
+            12 => Self::XiaZhi,
+            13 => Self::XiaoShu,
+            14 => Self::DaShu,
+            15 => Self::LiQiu,
+            16 => Self::ChuShu,
+            17 => Self::BaiLu,
+            18 => Self::QiuFen,
+            19 => Self::HanLu,
+            20 => Self::ShuangJiang,
+            21 => Self::LiDong,
+            22 => Self::XiaoXue,
+            23 => Self::DaXue,
+            24 => Self::DongZhi,
+            _ => unreachable!("bug!"),
+        }
+    }
+}
+
+impl FromData<u64> for MonthWeek {
+    fn from_data(val: u64) -> Self {
+        assert!(val < 7 && val != 0);
+        match val {
+            1 => Self::First,
+            2 => Self::Second,
+            3 => Self::Third,
+            4 => Self::Fourth,
+            5 => Self::Fifth,
+            6 => Self::Last,
+            _ => unreachable!("bug!"),
+        }
+    }
+}
+impl FromData<u64> for IsoWeek {
+    fn from_data(val: u64) -> Self {
+        assert!(val < 54 && val != 0);
+        match val {
+            1 => Self::Wk1,
+            2 => Self::Wk2,
+            3 => Self::Wk3,
+            4 => Self::Wk4,
+            5 => Self::Wk5,
+            6 => Self::Wk6,
+            7 => Self::Wk7,
+            8 => Self::Wk8,
+            9 => Self::Wk9,
+            10 => Self::Wk10,
+            11 => Self::Wk11,
+            12 => Self::Wk12,
+            13 => Self::Wk13,
+            14 => Self::Wk14,
+            15 => Self::Wk15,
+            16 => Self::Wk16,
+            17 => Self::Wk17,
+            18 => Self::Wk18,
+            19 => Self::Wk19,
+            20 => Self::Wk20,
+            21 => Self::Wk21,
+            22 => Self::Wk22,
+            23 => Self::Wk23,
+            24 => Self::Wk24,
+            25 => Self::Wk25,
+            26 => Self::Wk26,
+            27 => Self::Wk27,
+            28 => Self::Wk28,
+            29 => Self::Wk29,
+            30 => Self::Wk30,
+            31 => Self::Wk31,
+            32 => Self::Wk32,
+            33 => Self::Wk33,
+            34 => Self::Wk34,
+            35 => Self::Wk35,
+            36 => Self::Wk36,
+            37 => Self::Wk37,
+            38 => Self::Wk38,
+            39 => Self::Wk39,
+            40 => Self::Wk40,
+            41 => Self::Wk41,
+            42 => Self::Wk42,
+            43 => Self::Wk43,
+            44 => Self::Wk44,
+            45 => Self::Wk45,
+            46 => Self::Wk46,
+            47 => Self::Wk47,
+            48 => Self::Wk48,
+            49 => Self::Wk49,
+            50 => Self::Wk50,
+            51 => Self::Wk51,
+            52 => Self::Wk52,
+            53 => Self::Wk53,
+            _ => unreachable!("bug!"),
+        }
+    }
+}
+
 impl FromData<u64> for Hour {
     fn from_data(val: u64) -> Self {
         assert!(val < 24);
@@ -537,40 +887,99 @@ impl FromData<u64> for Second {
 impl TryFromData<u64> for MonthDay {
     fn try_from_data(val: u64) -> Result<Self> {
         if val == 0 || val > 31 {
-            bail!("month day should not be 0 or > 31");
+            return Err(Error::out_of_range("month day", val, 1, 31));
         }
         Ok(MonthDay::from_data(val))
     }
 }
 impl TryFromData<u64> for WeekDay {
     fn try_from_data(val: u64) -> Result<Self> {
-        if val >= 8 || val == 0 {
-            bail!("week day should not be 0 or >= 60");
+        if val == 0 || val > 7 {
+            return Err(Error::out_of_range("week day", val, 1, 7));
         }
         Ok(WeekDay::from_data(val))
     }
 }
+impl TryFromData<u64> for LunarMonth {
+    fn try_from_data(val: u64) -> Result<Self> {
+        if val == 0 || val > 12 {
+            return Err(Error::out_of_range("lunar month", val, 1, 12));
+        }
+        Ok(LunarMonth::from_data(val))
+    }
+}
+impl TryFromData<u64> for SolarTerm {
+    fn try_from_data(val: u64) -> Result<Self> {
+        if val == 0 || val > 24 {
+            return Err(Error::out_of_range("solar term", val, 1, 24));
+        }
+        Ok(SolarTerm::from_data(val))
+    }
+}
+impl TryFromData<u64> for MonthWeek {
+    fn try_from_data(val: u64) -> Result<Self> {
+        if val == 0 || val > 6 {
+            return Err(Error::out_of_range("month week", val, 1, 6));
+        }
+        Ok(MonthWeek::from_data(val))
+    }
+}
+impl TryFromData<u64> for IsoWeek {
+    fn try_from_data(val: u64) -> Result<Self> {
+        if val == 0 || val > 53 {
+            return Err(Error::out_of_range("iso week", val, 1, 53));
+        }
+        Ok(IsoWeek::from_data(val))
+    }
+}
 impl TryFromData<u64> for Hour {
     fn try_from_data(val: u64) -> Result<Self> {
-        if val >= 24 {
-            bail!("week day should not >= 24");
+        if val > 23 {
+            return Err(Error::out_of_range("hour", val, 0, 23));
         }
         Ok(Hour::from_data(val))
     }
 }
 impl TryFromData<u64> for Minuter {
     fn try_from_data(val: u64) -> Result<Self> {
-        if val >= 60 {
-            bail!("minuter should not >= 60");
+        if val > 59 {
+            return Err(Error::out_of_range("minuter", val, 0, 59));
         }
         Ok(Minuter::from_data(val))
     }
 }
 impl TryFromData<u64> for Second {
     fn try_from_data(val: u64) -> Result<Self> {
-        if val >= 60 {
-            bail!("second should not >= 60");
+        if val > 59 {
+            return Err(Error::out_of_range("second", val, 0, 59));
         }
         Ok(Second::from_data(val))
     }
 }
+
+macro_rules! impl_try_from {
+    ($($ty:ty),*) => {
+        $(
+            impl TryFrom<u64> for $ty {
+                type Error = Error;
+                fn try_from(val: u64) -> Result<Self> {
+                    Self::try_from_data(val)
+                }
+            }
+            impl TryFrom<u32> for $ty {
+                type Error = Error;
+                fn try_from(val: u32) -> Result<Self> {
+                    Self::try_from_data(val as u64)
+                }
+            }
+            impl TryFrom<u8> for $ty {
+                type Error = Error;
+                fn try_from(val: u8) -> Result<Self> {
+                    Self::try_from_data(val as u64)
+                }
+            }
+        )*
+    };
+}
+impl_try_from!(MonthDay, WeekDay, LunarMonth, SolarTerm, MonthWeek, IsoWeek, Hour, Minuter, Second);
+
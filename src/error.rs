@@ -0,0 +1,171 @@
+use std::fmt::{Display, Formatter};
+
+pub(crate) type Result<T> = std::result::Result<T, Error>;
+
+/// 错误类型。之后可能增加新的错误，`match`时需要`_`分支
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// 配置值超出了field的取值范围min..=max
+    OutOfRange {
+        field: &'static str,
+        value: u64,
+        min: u64,
+        max: u64,
+    },
+    /// 区间为空：起点晚于终点
+    EmptyRange {
+        field: &'static str,
+        start: String,
+        end: String,
+    },
+    /// 区间的端点无效，如不限制结束的日期时间范围、结束端点不包含最小值
+    InvalidBound {
+        field: &'static str,
+        reason: &'static str,
+    },
+    /// 步长无效，如every/add_range_step的步长为0
+    InvalidStep { field: &'static str, step: u64 },
+    /// 解析多行文本出错，line为出错的行（从1开始）
+    Parse { line: usize, message: String },
+    /// 解析单个字符串出错，如DaysMode、星期、RRULE的文本，没有行号
+    ParseText { message: String },
+    /// 定时配置永远不会触发，如某项一个值都没有选中
+    Unsatisfiable { field: &'static str },
+    /// 读取文件出错
+    Io(std::io::Error),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::OutOfRange {
+                field,
+                value,
+                min,
+                max,
+            } => write!(
+                f,
+                "{} should be in {}..={}, found {}",
+                field, min, max, value
+            ),
+            Error::EmptyRange { field, start, end } => {
+                write!(f, "{} range is empty: {} > {}", field, start, end)
+            }
+            Error::InvalidBound { field, reason } => {
+                write!(f, "invalid {} range bound: {}", field, reason)
+            }
+            Error::InvalidStep { field, step } => {
+                write!(f, "invalid {} step: {}", field, step)
+            }
+            Error::Parse { line, message } => write!(f, "line {}: {}", line, message),
+            Error::ParseText { message } => write!(f, "{}", message),
+            Error::Unsatisfiable { field } => write!(f, "no {} is selected", field),
+            Error::Io(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl Error {
+    pub(crate) fn out_of_range(field: &'static str, value: u64, min: u64, max: u64) -> Self {
+        Error::OutOfRange {
+            field,
+            value,
+            min,
+            max,
+        }
+    }
+    pub(crate) fn empty_range(field: &'static str, start: impl Display, end: impl Display) -> Self {
+        Error::EmptyRange {
+            field,
+            start: start.to_string(),
+            end: end.to_string(),
+        }
+    }
+    pub(crate) fn parse(index: usize, message: impl Into<String>) -> Self {
+        Error::Parse {
+            line: index + 1,
+            message: message.into(),
+        }
+    }
+    pub(crate) fn parse_text(message: impl Into<String>) -> Self {
+        Error::ParseText {
+            message: message.into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::conf::test::datetime;
+    use crate::*;
+
+    #[test]
+    fn test_error() {
+        assert!(matches!(
+            Hours::default_range(H5..H3),
+            Err(Error::EmptyRange { field: "hour", .. })
+        ));
+        assert!(matches!(
+            Hours::default_range(..H0),
+            Err(Error::InvalidBound { field: "hour", .. })
+        ));
+        let err = WeekDay::try_from_data(8).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::OutOfRange {
+                field: "week day",
+                value: 8,
+                min: 1,
+                max: 7
+            }
+        ));
+        assert_eq!(err.to_string(), "week day should be in 1..=7, found 8");
+
+        let conf = configure_monthday(MonthDays::default_all())
+            .build_with_hours(Hours::default_value(H0))
+            .build_with_minuter(Minuters::default_value(M0))
+            .build_with_second(Seconds::default_value(S0));
+        assert!(matches!(
+            conf.datetimes(datetime(2022, 1, 1, 0, 0, 0)..),
+            Err(Error::InvalidBound {
+                field: "datetime",
+                ..
+            })
+        ));
+        assert!(matches!(
+            conf.datetimes(datetime(2022, 1, 2, 0, 0, 0)..datetime(2022, 1, 1, 0, 0, 0)),
+            Err(Error::EmptyRange {
+                field: "datetime",
+                ..
+            })
+        ));
+        assert!(matches!(
+            ChinaCalendar::parse("# 2024\n2024-02-30 休\n"),
+            Err(Error::Parse { line: 2, .. })
+        ));
+        // 单个字符串没有行号
+        let err = "xor".parse::<DaysMode>().unwrap_err();
+        assert!(matches!(err, Error::ParseText { .. }));
+        assert_eq!(err.to_string(), "expected `or` or `and`, found `xor`");
+        assert!(matches!(
+            DateListCalendar::load("/nonexistent/holidays.txt"),
+            Err(Error::Io(_))
+        ));
+    }
+}
@@ -0,0 +1,473 @@
+use crate::combinator::{earlier, later};
+use crate::compute::{month_len, MonthMasks};
+use crate::conf::Days;
+use crate::schedule::{horizon, Schedule};
+use crate::traits::{bits_from, bits_to, highest, lowest};
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime};
+use std::collections::BTreeSet;
+
+/// 屏蔽规则：落在屏蔽时段内的时间点不触发
+#[derive(Debug, Clone)]
+pub enum Exclusion {
+    /// 指定的日期时间区间（闭区间）
+    Range(NaiveDateTime, NaiveDateTime),
+    /// 指定的日期（整天）
+    Dates(BTreeSet<NaiveDate>),
+    /// days选中的日期里，start..=end的时段。start晚于end时表示跨天，如22:00到次日02:00
+    Period {
+        days: Days,
+        start: NaiveTime,
+        end: NaiveTime,
+    },
+    /// 每月最后n天里，start..=end的时段，如月末结账。start晚于end时表示跨天
+    MonthEnd {
+        days: u32,
+        start: NaiveTime,
+        end: NaiveTime,
+    },
+}
+
+impl Exclusion {
+    /// 指定的日期（整天）
+    pub fn dates(dates: &[NaiveDate]) -> Self {
+        Self::Dates(dates.iter().copied().collect())
+    }
+    /// 若datetime被屏蔽，返回所在屏蔽时段的最后一秒
+    pub(crate) fn blackout_end(&self, datetime: NaiveDateTime) -> Option<NaiveDateTime> {
+        match self {
+            Exclusion::Range(start, end) => {
+                (*start <= datetime && datetime <= *end).then_some(*end)
+            }
+            Exclusion::Dates(dates) => {
+                let mut date = datetime.date();
+                if !dates.contains(&date) {
+                    return None;
+                }
+                // 连续的屏蔽日期一次跳过
+                while dates.contains(&date.succ()) {
+                    date = date.succ();
+                }
+                Some(date.and_hms(23, 59, 59))
+            }
+            Exclusion::Period { days, start, end } => {
+                period(datetime, *start, *end, |date| days.contain(date)).map(|(_, end)| end)
+            }
+            Exclusion::MonthEnd { days, start, end } => {
+                period(datetime, *start, *end, |date| is_month_end(date, *days)).map(|(_, end)| end)
+            }
+        }
+    }
+    /// 若datetime被屏蔽，返回所在屏蔽时段的第一秒
+    pub(crate) fn blackout_start(&self, datetime: NaiveDateTime) -> Option<NaiveDateTime> {
+        match self {
+            Exclusion::Range(start, end) => {
+                (*start <= datetime && datetime <= *end).then_some(*start)
+            }
+            Exclusion::Dates(dates) => {
+                let mut date = datetime.date();
+                if !dates.contains(&date) {
+                    return None;
+                }
+                while dates.contains(&date.pred()) {
+                    date = date.pred();
+                }
+                Some(date.and_hms(0, 0, 0))
+            }
+            Exclusion::Period { days, start, end } => {
+                period(datetime, *start, *end, |date| days.contain(date)).map(|(start, _)| start)
+            }
+            Exclusion::MonthEnd { days, start, end } => {
+                period(datetime, *start, *end, |date| is_month_end(date, *days))
+                    .map(|(start, _)| start)
+            }
+        }
+    }
+    /// 不早于datetime的第一个被屏蔽的时间点，limit所在的月份之后不再查找
+    pub(crate) fn next_blackout(
+        &self,
+        datetime: NaiveDateTime,
+        limit: NaiveDateTime,
+    ) -> Option<NaiveDateTime> {
+        match self {
+            Exclusion::Range(start, end) => (datetime <= *end).then(|| datetime.max(*start)),
+            Exclusion::Dates(dates) => {
+                let date = *dates.range(datetime.date()..).next()?;
+                Some(match date == datetime.date() {
+                    true => datetime,
+                    false => date.and_hms(0, 0, 0),
+                })
+            }
+            Exclusion::Period { days, start, end } => {
+                let mut masks = MonthMasks::new(days.clone());
+                next_period(
+                    datetime,
+                    (*start, *end, limit),
+                    |date| days.contain(date),
+                    |year, month| masks.mask_mut(year, month),
+                )
+            }
+            Exclusion::MonthEnd { days, start, end } => next_period(
+                datetime,
+                (*start, *end, limit),
+                |date| is_month_end(date, *days),
+                |year, month| month_end(year, month, *days),
+            ),
+        }
+    }
+    /// 不晚于datetime的最后一个被屏蔽的时间点，limit所在的月份之前不再查找
+    pub(crate) fn prev_blackout(
+        &self,
+        datetime: NaiveDateTime,
+        limit: NaiveDateTime,
+    ) -> Option<NaiveDateTime> {
+        match self {
+            Exclusion::Range(start, end) => (*start <= datetime).then(|| datetime.min(*end)),
+            Exclusion::Dates(dates) => {
+                let date = *dates.range(..=datetime.date()).next_back()?;
+                Some(match date == datetime.date() {
+                    true => datetime,
+                    false => date.and_hms(23, 59, 59),
+                })
+            }
+            Exclusion::Period { days, start, end } => {
+                let mut masks = MonthMasks::new(days.clone());
+                prev_period(
+                    datetime,
+                    (*start, *end, limit),
+                    |date| days.contain(date),
+                    |year, month| masks.mask_mut(year, month),
+                )
+            }
+            Exclusion::MonthEnd { days, start, end } => prev_period(
+                datetime,
+                (*start, *end, limit),
+                |date| is_month_end(date, *days),
+                |year, month| month_end(year, month, *days),
+            ),
+        }
+    }
+}
+
+/// 不早于datetime的第一个落在选中日期start..=end时段内的时间点。
+/// contain判断单个日期是否选中，mask给出当月选中日期的掩码（第i位为1表示选中i号）
+fn next_period(
+    datetime: NaiveDateTime,
+    (start, end, limit): (NaiveTime, NaiveTime, NaiveDateTime),
+    contain: impl Fn(NaiveDate) -> bool,
+    mut mask: impl FnMut(i32, u32) -> u64,
+) -> Option<NaiveDateTime> {
+    if period(datetime, start, end, &contain).is_some() {
+        return Some(datetime);
+    }
+    // 不在时段内时，下个时段从之后第一个选中日期的start开始
+    let date = datetime.date();
+    let (mut year, mut month) = (date.year(), date.month());
+    let mut day = match datetime.time() <= start {
+        true => date.day(),
+        false => date.day() + 1,
+    };
+    while (year, month) <= (limit.year(), limit.month()) {
+        if let Some(selected) = lowest(mask(year, month) & bits_from(day)) {
+            return Some(NaiveDate::from_ymd_opt(year, month, selected as u32)?.and_time(start));
+        }
+        if month == 12 {
+            year += 1;
+            month = 1;
+        } else {
+            month += 1;
+        }
+        day = 1;
+    }
+    None
+}
+
+/// 不晚于datetime的最后一个落在选中日期start..=end时段内的时间点，见[`next_period`]
+fn prev_period(
+    datetime: NaiveDateTime,
+    (start, end, limit): (NaiveTime, NaiveTime, NaiveDateTime),
+    contain: impl Fn(NaiveDate) -> bool,
+    mut mask: impl FnMut(i32, u32) -> u64,
+) -> Option<NaiveDateTime> {
+    if period(datetime, start, end, &contain).is_some() {
+        return Some(datetime);
+    }
+    // 不在时段内时，上个时段在end结束；跨天的时段在选中日期的次日结束
+    let overnight = (start > end) as i64;
+    let last = match datetime.time() < end {
+        true => datetime.date().pred(),
+        false => datetime.date(),
+    };
+    let last = last.checked_sub_signed(Duration::days(overnight))?;
+    let (mut year, mut month) = (last.year(), last.month());
+    let mut day = last.day();
+    while (year, month) >= (limit.year(), limit.month()) {
+        if let Some(selected) = highest(mask(year, month) & bits_to(day)) {
+            let date = NaiveDate::from_ymd_opt(year, month, selected as u32)?;
+            return Some(
+                date.checked_add_signed(Duration::days(overnight))?
+                    .and_time(end),
+            );
+        }
+        if month == 1 {
+            year -= 1;
+            month = 12;
+        } else {
+            month -= 1;
+        }
+        day = 31;
+    }
+    None
+}
+
+/// 当月最后days天的掩码
+fn month_end(year: i32, month: u32, days: u32) -> u64 {
+    let len = month_len(year, month);
+    match days {
+        0 => 0,
+        _ => bits_from((len + 1).saturating_sub(days).max(1)) & bits_to(len),
+    }
+}
+
+/// 若datetime落在某个选中日期的start..=end时段内，返回该时段的起止
+fn period(
+    datetime: NaiveDateTime,
+    start: NaiveTime,
+    end: NaiveTime,
+    contain: impl Fn(NaiveDate) -> bool,
+) -> Option<(NaiveDateTime, NaiveDateTime)> {
+    let date = datetime.date();
+    let time = datetime.time();
+    if start <= end {
+        (start <= time && time <= end && contain(date)).then(|| {
+            (
+                NaiveDateTime::new(date, start),
+                NaiveDateTime::new(date, end),
+            )
+        })
+    } else if time >= start && contain(date) {
+        // 跨天：从当天start到次日end
+        Some((
+            NaiveDateTime::new(date, start),
+            NaiveDateTime::new(date.succ(), end),
+        ))
+    } else if time <= end && contain(date.pred()) {
+        Some((
+            NaiveDateTime::new(date.pred(), start),
+            NaiveDateTime::new(date, end),
+        ))
+    } else {
+        None
+    }
+}
+
+/// 是否为当月的最后days天
+fn is_month_end(date: NaiveDate, days: u32) -> bool {
+    let mut last = date;
+    for _ in 0..days {
+        last = last.succ();
+        if last.month() != date.month() {
+            return true;
+        }
+    }
+    false
+}
+
+/// 带屏蔽规则的定时计划：跳过所有屏蔽时段
+#[derive(Debug, Clone)]
+pub struct Excluded<S: Schedule> {
+    schedule: S,
+    exclusions: Vec<Exclusion>,
+}
+
+impl<S: Schedule> Excluded<S> {
+    pub fn new(schedule: S) -> Self {
+        Self {
+            schedule,
+            exclusions: Vec::new(),
+        }
+    }
+    /// 增加屏蔽规则
+    pub fn exclude(mut self, exclusion: Exclusion) -> Self {
+        self.exclusions.push(exclusion);
+        self
+    }
+    fn blackout_end(&self, datetime: NaiveDateTime) -> Option<NaiveDateTime> {
+        self.exclusions
+            .iter()
+            .filter_map(|x| x.blackout_end(datetime))
+            .max()
+    }
+    fn blackout_start(&self, datetime: NaiveDateTime) -> Option<NaiveDateTime> {
+        self.exclusions
+            .iter()
+            .filter_map(|x| x.blackout_start(datetime))
+            .min()
+    }
+}
+
+impl<S: Schedule> Schedule for Excluded<S> {
+    /// 候选时间点被屏蔽时，直接从屏蔽时段的结束处继续查找，而不是逐个过滤
+    fn next_with_time(&self, mut now: NaiveDateTime) -> Option<NaiveDateTime> {
+        let limit = horizon(now, true);
+        loop {
+            let next = self.schedule.next_with_time(now)?;
+            match self.blackout_end(next) {
+                None => return Some(next),
+                Some(end) if end > limit => return None,
+                Some(end) => now = end,
+            }
+        }
+    }
+    fn prev_with_time(&self, mut now: NaiveDateTime) -> Option<NaiveDateTime> {
+        let limit = horizon(now, false);
+        loop {
+            let prev = self.schedule.prev_with_time(now)?;
+            match self.blackout_start(prev) {
+                None => return Some(prev),
+                Some(start) if start < limit => return None,
+                Some(start) => now = start,
+            }
+        }
+    }
+    fn matches(&self, datetime: NaiveDateTime) -> bool {
+        self.schedule.matches(datetime) && self.blackout_end(datetime).is_none()
+    }
+    /// 原计划不符合，或进入屏蔽时段即不符合，取两者中较早的那个
+    fn next_unmatched(&self, now: NaiveDateTime) -> Option<NaiveDateTime> {
+        let next = now.checked_add_signed(Duration::seconds(1))?;
+        let limit = horizon(now, true);
+        self.exclusions
+            .iter()
+            .map(|x| x.next_blackout(next, limit))
+            .fold(self.schedule.next_unmatched(now), earlier)
+    }
+    fn prev_unmatched(&self, now: NaiveDateTime) -> Option<NaiveDateTime> {
+        let prev = now.checked_sub_signed(Duration::seconds(1))?;
+        let limit = horizon(now, false);
+        self.exclusions
+            .iter()
+            .map(|x| x.prev_blackout(prev, limit))
+            .fold(self.schedule.prev_unmatched(now), later)
+    }
+    fn valid_range(&self) -> (Option<NaiveDateTime>, Option<NaiveDateTime>) {
+        self.schedule.valid_range()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Exclusion;
+    use crate::conf::test::{check_unmatched, datetime};
+    use crate::*;
+    use chrono::{NaiveDate, NaiveTime};
+
+    #[test]
+    fn test_month_end() -> anyhow::Result<()> {
+        // 每天18:00起每小时一次，月末3天18:00-23:59不执行
+        let conf = configure_monthday(MonthDays::default_all())
+            .build_with_hours(Hours::default_range(H18..=H23)?)
+            .build_with_minuter(Minuters::default_value(M0))
+            .build_with_second(Seconds::default_value(S0));
+        let conf = conf.exclude(Exclusion::MonthEnd {
+            days: 3,
+            start: NaiveTime::from_hms(18, 0, 0),
+            end: NaiveTime::from_hms(23, 59, 59),
+        });
+        assert_eq!(
+            conf.next_with_time(datetime(2022, 2, 25, 23, 0, 0)),
+            Some(datetime(2022, 3, 1, 18, 0, 0))
+        );
+        assert_eq!(
+            conf.prev_with_time(datetime(2022, 3, 1, 18, 0, 0)),
+            Some(datetime(2022, 2, 25, 23, 0, 0))
+        );
+        assert!(!conf.matches(datetime(2022, 3, 31, 18, 0, 0)));
+        assert!(conf.matches(datetime(2022, 3, 28, 18, 0, 0)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_dates_and_period() -> anyhow::Result<()> {
+        let conf = configure_monthday(MonthDays::default_all())
+            .build_with_hours(Hours::default_value(H1))
+            .build_with_minuter(Minuters::default_value(M0))
+            .build_with_second(Seconds::default_value(S0))
+            .exclude(Exclusion::dates(&[
+                NaiveDate::from_ymd(2022, 10, 1),
+                NaiveDate::from_ymd(2022, 10, 2),
+                NaiveDate::from_ymd(2022, 10, 3),
+            ]))
+            // 周六晚22点到周日凌晨2点
+            .exclude(
+                configure_weekday(WeekDays::default_value(W6))
+                    .build_exclusion(NaiveTime::from_hms(22, 0, 0), NaiveTime::from_hms(2, 0, 0)),
+            )
+            .exclude(Exclusion::Range(
+                datetime(2022, 10, 12, 0, 0, 0),
+                datetime(2022, 10, 13, 12, 0, 0),
+            ));
+        assert_eq!(
+            conf.datetimes(datetime(2022, 9, 30, 0, 0, 0)..datetime(2022, 10, 14, 0, 0, 0))?,
+            vec![
+                datetime(2022, 9, 30, 1, 0, 0),
+                datetime(2022, 10, 4, 1, 0, 0),
+                datetime(2022, 10, 5, 1, 0, 0),
+                datetime(2022, 10, 6, 1, 0, 0),
+                datetime(2022, 10, 7, 1, 0, 0),
+                datetime(2022, 10, 8, 1, 0, 0),
+                datetime(2022, 10, 10, 1, 0, 0),
+                datetime(2022, 10, 11, 1, 0, 0),
+            ]
+        );
+        assert_eq!(
+            conf.prev_with_time(datetime(2022, 10, 4, 1, 0, 0)),
+            Some(datetime(2022, 9, 30, 1, 0, 0))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_unmatched() -> anyhow::Result<()> {
+        // 一直符合的计划，只有屏蔽时段不符合。2022-10-01是周六
+        let conf = configure_monthday(MonthDays::default_all())
+            .build_with_hours(Hours::default_all())
+            .build_with_minuter(Minuters::default_all())
+            .build_with_second(Seconds::default_all())
+            .exclude(Exclusion::dates(&[
+                NaiveDate::from_ymd(2022, 10, 1),
+                NaiveDate::from_ymd(2022, 10, 2),
+            ]))
+            .exclude(
+                configure_weekday(WeekDays::default_value(W6))
+                    .build_exclusion(NaiveTime::from_hms(22, 0, 0), NaiveTime::from_hms(2, 0, 0)),
+            )
+            .exclude(
+                configure_weekday(WeekDays::default_value(W3))
+                    .build_exclusion(NaiveTime::from_hms(12, 0, 0), NaiveTime::from_hms(13, 0, 0)),
+            )
+            .exclude(Exclusion::Range(
+                datetime(2022, 10, 13, 0, 0, 0),
+                datetime(2022, 10, 13, 12, 0, 0),
+            ))
+            .exclude(Exclusion::MonthEnd {
+                days: 3,
+                start: NaiveTime::from_hms(18, 0, 0),
+                end: NaiveTime::from_hms(23, 59, 59),
+            });
+        for now in [
+            datetime(2022, 9, 28, 17, 0, 0),
+            datetime(2022, 9, 30, 20, 0, 0),
+            datetime(2022, 10, 3, 12, 0, 0),
+            datetime(2022, 10, 5, 12, 30, 0),
+            datetime(2022, 10, 6, 13, 0, 1),
+            datetime(2022, 10, 9, 1, 0, 0),
+            datetime(2022, 10, 9, 3, 0, 0),
+            datetime(2022, 10, 12, 23, 0, 0),
+            datetime(2022, 10, 13, 12, 0, 0),
+            datetime(2022, 10, 29, 17, 59, 59),
+        ] {
+            check_unmatched(&conf, now, 3);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,472 @@
+use crate::compute::{month_len, MonthMasks};
+use crate::conf::{Days, Hours, Minuters, Seconds, TimerConf};
+use crate::data::{MonthDay, WeekDay};
+use crate::traits::{bits_from, bits_to, lowest, ConfigOperator, FromData};
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+use std::fmt::{Display, Formatter};
+use std::ops::Add;
+
+/// 下个时间点的查找过程，用于排查定时器为什么在某个时间点触发，见[`TimerConf::explain_next`]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Explanation {
+    /// 查找的起点（不包含）
+    pub now: NaiveDateTime,
+    /// 下个时间点，400年内都没有则为None
+    pub next: Option<NaiveDateTime>,
+    /// 选中的日期及其来源
+    pub day: Option<DayMatch>,
+    /// 选中的时、分、秒
+    pub times: Vec<FieldMatch>,
+    /// 起点到选中的日期之间跳过的日期，相邻且原因相同的合并为一段
+    pub skipped: Vec<Skipped>,
+}
+
+/// 选中的日期
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct DayMatch {
+    pub date: NaiveDate,
+    /// 由哪项日期配置选中
+    pub source: DaySource,
+    /// 当月选中的日期（即[`Days`]按当月展开的结果），第i位为1表示选中i号
+    pub bits: u64,
+}
+
+/// 日期由哪项配置选中
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum DaySource {
+    /// 月日期配置
+    MonthDays,
+    /// 星期配置按当月1号是星期几展开后选中
+    WeekDays,
+    /// 月日期与星期配置都选中
+    Both,
+    /// 其他日期配置，如工作日、农历、节气，值为配置的名称
+    Rule(&'static str),
+}
+
+/// 选中的时、分或秒
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct FieldMatch {
+    /// 字段名，即[`ConfigOperator::NAME`]
+    pub field: &'static str,
+    /// 选中的值
+    pub value: u64,
+    /// 字段的配置，第i位为1表示选中i
+    pub bits: u64,
+}
+
+/// 跳过的一段日期：start..=end
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Skipped {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub reason: SkipReason,
+}
+
+/// 跳过日期的原因
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum SkipReason {
+    /// 日期配置没有选中
+    NotSelected,
+    /// 日期被选中，但当天起点之后没有符合的时刻
+    TimePassed,
+}
+
+impl TimerConf {
+    /// 以给定的时间点为起点(不包含该时点)查找下个时间点，并记录查找过程：
+    /// 选中的日期及其来源、时分秒各自选中的值、中间跳过的日期及原因。
+    ///
+    /// 下个时间点就是[`Schedule::next_with_time`](crate::Schedule::next_with_time)的结果，
+    /// 之后按月展开日期配置补上跳过的日期；没有下个时间点时不记录跳过的日期
+    pub fn explain_next(&self, now: NaiveDateTime) -> Explanation {
+        let mut explanation = Explanation {
+            now,
+            next: self.find_next(now),
+            day: None,
+            times: Vec::new(),
+            skipped: Vec::new(),
+        };
+        let (start, next) = match explanation.next {
+            Some(next) => (now.add(Duration::seconds(1)).date(), next),
+            None => return explanation,
+        };
+        let mut masks = MonthMasks::new(self.days.clone());
+        let (mut year, mut month) = (start.year(), start.month());
+        let mut day = start.day();
+        loop {
+            let bits = masks.mask_mut(year, month);
+            if (year, month) == (next.year(), next.month()) {
+                explanation.skip_month(year, month, bits, day, next.day() - 1);
+                explanation.found(self, next.date(), bits, next.time());
+                return explanation;
+            }
+            explanation.skip_month(year, month, bits, day, month_len(year, month));
+            if month == 12 {
+                year += 1;
+                month = 1;
+            } else {
+                month += 1;
+            }
+            day = 1;
+        }
+    }
+}
+
+impl Explanation {
+    /// 记录当月first..=last日跳过的日期：没有选中的，及选中但已没有符合的时刻的
+    fn skip_month(&mut self, year: i32, month: u32, bits: u64, first: u32, last: u32) {
+        let date = |day: u32| NaiveDate::from_ymd(year, month, day);
+        let mut day = first;
+        let mut selected = bits & bits_from(first) & bits_to(last);
+        while let Some(next) = lowest(selected) {
+            selected &= selected - 1;
+            let next = next as u32;
+            if day < next {
+                self.skip(date(day), date(next - 1), SkipReason::NotSelected);
+            }
+            self.skip(date(next), date(next), SkipReason::TimePassed);
+            day = next + 1;
+        }
+        if day <= last {
+            self.skip(date(day), date(last), SkipReason::NotSelected);
+        }
+    }
+    fn skip(&mut self, start: NaiveDate, end: NaiveDate, reason: SkipReason) {
+        if let Some(last) = self.skipped.last_mut() {
+            if last.reason == reason && last.end.succ() == start {
+                last.end = end;
+                return;
+            }
+        }
+        self.skipped.push(Skipped { start, end, reason });
+    }
+    fn found(&mut self, conf: &TimerConf, date: NaiveDate, bits: u64, time: NaiveTime) {
+        self.next = Some(NaiveDateTime::new(date, time));
+        self.day = Some(DayMatch {
+            date,
+            source: source(&conf.days, date),
+            bits,
+        });
+        self.times = vec![
+            FieldMatch {
+                field: Hours::NAME,
+                value: time.hour() as u64,
+                bits: conf.hours._val() & Hours::_mask(),
+            },
+            FieldMatch {
+                field: Minuters::NAME,
+                value: time.minute() as u64,
+                bits: conf.minuters._val() & Minuters::_mask(),
+            },
+            FieldMatch {
+                field: Seconds::NAME,
+                value: time.second() as u64,
+                bits: conf.seconds._val() & Seconds::_mask(),
+            },
+        ];
+    }
+}
+
+/// 选中的日期由哪项配置选中。月内第几周、ISO周、节假日调整只是筛选或挪动内层配置选中的日期，
+/// 任一配置时取选中该日期的那一项，都按内层配置说明；顺延/提前来的日期内层没有选中，按调整说明
+fn source(days: &Days, date: NaiveDate) -> DaySource {
+    match days {
+        Days::MonthDays(_) => DaySource::MonthDays,
+        Days::WeekDays(_) => DaySource::WeekDays,
+        Days::MonthAndWeekDays(month_days, week_days, _) => {
+            let by_month = month_days.contain(MonthDay::from_data(date.day() as u64));
+            let by_week = week_days.contain(WeekDay::from(date.weekday()));
+            match (by_month, by_week) {
+                (true, true) => DaySource::Both,
+                (true, false) => DaySource::MonthDays,
+                _ => DaySource::WeekDays,
+            }
+        }
+        Days::WorkDays(_) => DaySource::Rule("work days"),
+        Days::LunarDays(_) => DaySource::Rule("lunar days"),
+        Days::SolarTerms(_) => DaySource::Rule("solar terms"),
+        Days::YearDays(_) => DaySource::Rule("year days"),
+        Days::Any(list) => list
+            .iter()
+            .find(|days| days.contain(date))
+            .map_or(DaySource::Rule("any"), |days| source(days, date)),
+        Days::MonthWeeks { days, .. } | Days::IsoWeeks { days, .. } => source(days, date),
+        Days::Adjusted { days, .. } => match days.contain(date) {
+            true => source(days, date),
+            false => DaySource::Rule("adjusted"),
+        },
+    }
+}
+
+impl Display for Explanation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.next {
+            Some(next) => writeln!(f, "next after {}: {}", self.now, next)?,
+            None => writeln!(f, "next after {}: none", self.now)?,
+        }
+        for skipped in self.skipped.iter() {
+            let reason = match skipped.reason {
+                SkipReason::NotSelected => "not selected",
+                SkipReason::TimePassed => "no time left",
+            };
+            match skipped.start == skipped.end {
+                true => writeln!(f, "  skipped {}: {}", skipped.start, reason)?,
+                false => writeln!(
+                    f,
+                    "  skipped {}..={}: {}",
+                    skipped.start, skipped.end, reason
+                )?,
+            }
+        }
+        if let Some(day) = &self.day {
+            let source = match day.source {
+                DaySource::MonthDays => "month days",
+                DaySource::WeekDays => "week days",
+                DaySource::Both => "month days and week days",
+                DaySource::Rule(name) => name,
+            };
+            writeln!(
+                f,
+                "  day {} by {}, selected {:?}",
+                day.date,
+                source,
+                values(day.bits)
+            )?;
+        }
+        for time in self.times.iter() {
+            writeln!(
+                f,
+                "  {} {} in {:?}",
+                time.field,
+                time.value,
+                values(time.bits)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// 位图里选中的值
+fn values(mut bits: u64) -> Vec<u64> {
+    let mut values = Vec::new();
+    while let Some(value) = lowest(bits) {
+        bits &= bits - 1;
+        values.push(value);
+    }
+    values
+}
+
+#[cfg(test)]
+mod test {
+    use crate::conf::test::datetime;
+    use crate::conf::Days;
+    use crate::explain::{DaySource, SkipReason, Skipped};
+    use crate::lunar;
+    use crate::*;
+    use chrono::{Duration, NaiveDate};
+
+    #[test]
+    fn test_explain_next() -> anyhow::Result<()> {
+        // 每月13号及每个周五的9:30:00
+        let conf = configure_weekday(WeekDays::default_value(W5))
+            .conf_month_days(MonthDays::default_value(D13))
+            .build_with_hours(Hours::default_array(&[H9, H18]))
+            .build_with_minuter(Minuters::default_value(M30))
+            .build_with_second(Seconds::default_value(S0));
+        // 2024-09-13是周五，2024-09-20是周五
+        let explanation = conf.explain_next(datetime(2024, 9, 12, 20, 0, 0));
+        assert_eq!(explanation.next, Some(datetime(2024, 9, 13, 9, 30, 0)));
+        assert_eq!(explanation.day.unwrap().source, DaySource::Both);
+        // 起点当天不是周五、也不是13号
+        assert_eq!(explanation.skipped.len(), 1);
+        assert_eq!(explanation.skipped[0].reason, SkipReason::NotSelected);
+        let values: Vec<_> = explanation
+            .times
+            .iter()
+            .map(|x| (x.field, x.value))
+            .collect();
+        assert_eq!(values, vec![("hour", 9), ("minuter", 30), ("second", 0)]);
+        assert_eq!(explanation.times[0].bits, (1 << 9) | (1 << 18));
+
+        let explanation = conf.explain_next(datetime(2024, 9, 13, 18, 30, 0));
+        assert_eq!(explanation.next, Some(datetime(2024, 9, 20, 9, 30, 0)));
+        assert_eq!(explanation.day.unwrap().source, DaySource::WeekDays);
+        let date = |day| NaiveDate::from_ymd(2024, 9, day);
+        assert_eq!(
+            explanation.skipped,
+            vec![
+                Skipped {
+                    start: date(13),
+                    end: date(13),
+                    reason: SkipReason::TimePassed,
+                },
+                Skipped {
+                    start: date(14),
+                    end: date(19),
+                    reason: SkipReason::NotSelected,
+                },
+            ]
+        );
+        let text = explanation.to_string();
+        assert!(text.contains("skipped 2024-09-14..=2024-09-19: not selected"));
+        assert!(text.contains("day 2024-09-20 by week days"));
+
+        let explanation = conf.explain_next(datetime(2024, 11, 12, 20, 0, 0));
+        assert_eq!(explanation.day.unwrap().source, DaySource::MonthDays);
+
+        // 与next_with_time一致
+        let confs = [
+            conf,
+            configure_workday(ChinaCalendar::bundled())
+                .build_with_hours(Hours::default_value(H9))
+                .build_with_minuter(Minuters::every(20)?)
+                .build_with_second(Seconds::default_value(S0)),
+        ];
+        for conf in confs.iter() {
+            let mut now = datetime(2024, 1, 1, 0, 0, 0);
+            while now < datetime(2025, 1, 1, 0, 0, 0) {
+                assert_eq!(conf.explain_next(now).next, Some(conf.next_with_time(now)));
+                now += Duration::seconds(17 * 3600 + 7 * 60 + 3);
+            }
+        }
+        assert_eq!(
+            confs[1]
+                .explain_next(datetime(2024, 10, 1, 0, 0, 0))
+                .day
+                .unwrap()
+                .source,
+            DaySource::Rule("work days")
+        );
+
+        // 永远不会触发
+        let conf = configure_monthday(MonthDays::default_value(D1))
+            .build_with_hours(Hours::default())
+            .build_with_minuter(Minuters::default_value(M0))
+            .build_with_second(Seconds::default_value(S0));
+        let explanation = conf.explain_next(datetime(2024, 1, 1, 0, 0, 0));
+        assert_eq!(explanation.next, None);
+        assert_eq!(explanation.day, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_nested_source() -> anyhow::Result<()> {
+        let at = |days: Days| {
+            TimerConf::new(
+                days,
+                Hours::default_value(H9),
+                Minuters::default_value(M0),
+                Seconds::default_value(S0),
+            )
+        };
+        let source = |days: builder::DayConfBuilder, now| {
+            let conf = at(days.days);
+            let explanation = conf.explain_next(now);
+            (explanation.next, explanation.day.map(|x| x.source))
+        };
+        // 2024-09-13是周五，2024-09-14是周六
+        let now = datetime(2024, 9, 12, 20, 0, 0);
+        let fridays = configure_weekday(WeekDays::default_value(W5));
+        assert_eq!(
+            source(
+                fridays.conf_holidays(DateListCalendar::default(), HolidayPolicy::Skip),
+                now
+            ),
+            (
+                Some(datetime(2024, 9, 13, 9, 0, 0)),
+                Some(DaySource::WeekDays)
+            )
+        );
+        // 周六顺延到周一，周一不是内层选中的日期
+        let saturdays = configure_weekday(WeekDays::default_value(W6))
+            .conf_holidays(DateListCalendar::default(), HolidayPolicy::NextWorkday);
+        assert_eq!(
+            source(saturdays, now),
+            (
+                Some(datetime(2024, 9, 16, 9, 0, 0)),
+                Some(DaySource::Rule("adjusted"))
+            )
+        );
+        // 每月第1个周五
+        let first = configure_weekday(WeekDays::default_value(W5))
+            .conf_month_weeks(MonthWeeks::default_value(MonthWeek::First));
+        assert_eq!(
+            source(first, now),
+            (
+                Some(datetime(2024, 10, 4, 9, 0, 0)),
+                Some(DaySource::WeekDays)
+            )
+        );
+        let conf = at(Days::Any(vec![
+            Days::YearDays(YearDays::default_value(1)?),
+            Days::MonthDays(MonthDays::default_value(D13)),
+        ]));
+        let explanation = conf.explain_next(now);
+        assert_eq!(explanation.next, Some(datetime(2024, 9, 13, 9, 0, 0)));
+        assert_eq!(explanation.day.unwrap().source, DaySource::MonthDays);
+        let explanation = conf.explain_next(datetime(2024, 12, 31, 0, 0, 0));
+        assert_eq!(explanation.next, Some(datetime(2025, 1, 1, 9, 0, 0)));
+        assert_eq!(
+            explanation.day.unwrap().source,
+            DaySource::Rule("year days")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_explain_far() -> anyhow::Result<()> {
+        let at = |days: builder::DayConfBuilder| {
+            days.build_with_hours(Hours::default_value(H9))
+                .build_with_minuter(Minuters::default_value(M0))
+                .build_with_second(Seconds::default_value(S0))
+        };
+        // 闰年的最后一天：2096年之后跳过7年多，按月合并为一段
+        let conf = at(configure_yearday(YearDays::default_value(366)?));
+        let explanation = conf.explain_next(datetime(2096, 12, 31, 9, 0, 0));
+        assert_eq!(explanation.next, Some(datetime(2104, 12, 31, 9, 0, 0)));
+        assert_eq!(
+            explanation.skipped,
+            vec![
+                Skipped {
+                    start: NaiveDate::from_ymd(2096, 12, 31),
+                    end: NaiveDate::from_ymd(2096, 12, 31),
+                    reason: SkipReason::TimePassed,
+                },
+                Skipped {
+                    start: NaiveDate::from_ymd(2097, 1, 1),
+                    end: NaiveDate::from_ymd(2104, 12, 30),
+                    reason: SkipReason::NotSelected,
+                },
+            ]
+        );
+        assert_eq!(explanation.day.unwrap().bits, 1 << 31);
+
+        // 与next_with_time一致，包括超出农历表、永远不会触发的配置
+        let (_, last) = lunar::solar_years();
+        let confs = [
+            conf,
+            at(configure_lunar(LunarDays::default_value(L1, D1))),
+            at(configure_monthday(MonthDays::default_value(D31))
+                .conf_week_days(WeekDays::default_value(W1))
+                .conf_mode(DaysMode::And)),
+            at(configure_monthday(MonthDays::default_value(D30))
+                .conf_month_weeks(MonthWeeks::default_value(MonthWeek::First))),
+        ];
+        let times = [
+            datetime(2024, 1, 1, 0, 0, 0),
+            datetime(last - 1, 6, 1, 0, 0, 0),
+            datetime(last + 1, 1, 1, 0, 0, 0),
+        ];
+        for conf in confs.iter() {
+            for now in times {
+                let explanation = conf.explain_next(now);
+                assert_eq!(explanation.next, Schedule::next_with_time(conf, now));
+                assert_eq!(
+                    explanation.day.map(|x| x.date),
+                    explanation.next.map(|x| x.date())
+                );
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,121 @@
+use crate::schedule::Schedule;
+use chrono::NaiveDateTime;
+use std::collections::BTreeSet;
+use std::ops::Bound;
+
+/// 指定时间点的定时计划：只在给定的若干时间点触发，如数据迁移等一次性任务
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct FixedTimes(BTreeSet<NaiveDateTime>);
+
+impl FixedTimes {
+    /// 只触发一次的定时计划
+    pub fn once(datetime: NaiveDateTime) -> Self {
+        Self::default().add_time(datetime)
+    }
+    pub fn default_array(datetimes: &[NaiveDateTime]) -> Self {
+        datetimes.iter().copied().collect()
+    }
+    pub fn add_time(mut self, datetime: NaiveDateTime) -> Self {
+        self.0.insert(datetime);
+        self
+    }
+    pub fn remove(&mut self, datetime: &NaiveDateTime) -> bool {
+        self.0.remove(datetime)
+    }
+    pub fn contain(&self, datetime: &NaiveDateTime) -> bool {
+        self.0.contains(datetime)
+    }
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+    /// 最早的时间点
+    pub fn first(&self) -> Option<NaiveDateTime> {
+        self.0.iter().next().copied()
+    }
+    /// 最晚的时间点
+    pub fn last(&self) -> Option<NaiveDateTime> {
+        self.0.iter().next_back().copied()
+    }
+    /// 按时间先后遍历所有时间点
+    pub fn iter(&self) -> impl Iterator<Item = &NaiveDateTime> {
+        self.0.iter()
+    }
+}
+
+impl Schedule for FixedTimes {
+    fn next_with_time(&self, now: NaiveDateTime) -> Option<NaiveDateTime> {
+        self.0
+            .range((Bound::Excluded(now), Bound::Unbounded))
+            .next()
+            .copied()
+    }
+    fn prev_with_time(&self, now: NaiveDateTime) -> Option<NaiveDateTime> {
+        self.0
+            .range((Bound::Unbounded, Bound::Excluded(now)))
+            .next_back()
+            .copied()
+    }
+}
+
+impl FromIterator<NaiveDateTime> for FixedTimes {
+    fn from_iter<T: IntoIterator<Item = NaiveDateTime>>(iter: T) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl Extend<NaiveDateTime> for FixedTimes {
+    fn extend<T: IntoIterator<Item = NaiveDateTime>>(&mut self, iter: T) {
+        self.0.extend(iter)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::FixedTimes;
+    use crate::conf::test::datetime;
+    use crate::schedule::Schedule;
+
+    #[test]
+    fn test_fixed_times() -> anyhow::Result<()> {
+        let times = FixedTimes::default_array(&[
+            datetime(2022, 7, 5, 10, 0, 0),
+            datetime(2022, 7, 1, 10, 0, 0),
+            datetime(2022, 7, 3, 10, 0, 0),
+        ]);
+        assert_eq!(times.len(), 3);
+        assert_eq!(times.first(), Some(datetime(2022, 7, 1, 10, 0, 0)));
+        assert_eq!(times.last(), Some(datetime(2022, 7, 5, 10, 0, 0)));
+
+        assert_eq!(
+            times.next_with_time(datetime(2022, 6, 1, 0, 0, 0)),
+            Some(datetime(2022, 7, 1, 10, 0, 0))
+        );
+        // 不包含给定的时间点
+        assert_eq!(
+            times.next_with_time(datetime(2022, 7, 1, 10, 0, 0)),
+            Some(datetime(2022, 7, 3, 10, 0, 0))
+        );
+        assert_eq!(times.next_with_time(datetime(2022, 7, 5, 10, 0, 0)), None);
+        assert_eq!(
+            times.prev_with_time(datetime(2022, 7, 5, 10, 0, 0)),
+            Some(datetime(2022, 7, 3, 10, 0, 0))
+        );
+        assert_eq!(times.prev_with_time(datetime(2022, 7, 1, 10, 0, 0)), None);
+
+        assert_eq!(
+            times.datetimes(datetime(2022, 7, 1, 10, 0, 0)..datetime(2022, 7, 5, 10, 0, 0))?,
+            vec![datetime(2022, 7, 1, 10, 0, 0), datetime(2022, 7, 3, 10, 0, 0)]
+        );
+        assert_eq!(
+            times.iter_from(datetime(2022, 7, 1, 0, 0, 0)).collect::<Vec<_>>(),
+            times.iter().copied().collect::<Vec<_>>()
+        );
+
+        let once = FixedTimes::once(datetime(2022, 7, 1, 10, 0, 0));
+        assert_eq!(once.iter_from(datetime(2022, 7, 1, 10, 0, 0)).next(), None);
+        Ok(())
+    }
+}
@@ -0,0 +1,30 @@
+/// 稳定的哈希（FNV-1a），不随Rust版本、进程变化
+pub(crate) fn stable_hash(key: &str) -> u64 {
+    let hash = key.bytes().fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    });
+    mix(hash)
+}
+
+/// 打散哈希值的各位（splitmix64）
+pub(crate) fn mix(mut val: u64) -> u64 {
+    val = val.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    val = (val ^ (val >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    val = (val ^ (val >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    val ^ (val >> 31)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_stable_hash() {
+        // 哈希值会决定hashed配置选中的值，不能随版本改变
+        assert_eq!(stable_hash(""), mix(0xcbf2_9ce4_8422_2325));
+        assert_eq!(stable_hash("backup"), 0xfd8c_1160_4d55_6748);
+        assert_ne!(stable_hash("backup"), stable_hash("backup2"));
+        // splitmix64以0为种子的第一个输出
+        assert_eq!(mix(0), 0xe220_a839_7b1d_cdaf);
+    }
+}
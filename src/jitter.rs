@@ -0,0 +1,363 @@
+use crate::hash::{mix, stable_hash};
+use crate::schedule::{horizon, Schedule};
+use chrono::{Duration, NaiveDateTime};
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::ops::{Add, Sub};
+
+/// 抖动方式：把触发时间点往后推迟window以内（按秒），避免大量任务在同一时刻触发
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Jitter {
+    /// 每个时间点随机推迟。随机种子在创建时生成，同一实例对同一时间点的推迟总是相同的
+    Random(Duration),
+    /// 按key的哈希值固定推迟，同一个key（如任务名）在任何进程里都是相同的偏移，类似Jenkins的H
+    Hashed { key: String, window: Duration },
+}
+
+/// 抖动：触发时间点按[`Jitter`]往后推迟，见[`Schedule::jitter`]
+#[derive(Debug, Clone)]
+pub struct Jittered<S: Schedule> {
+    schedule: S,
+    // 推迟的范围：[0, window)秒
+    window: i64,
+    seed: u64,
+    // 是否每个时间点各自计算偏移
+    per_instant: bool,
+}
+
+impl<S: Schedule> Jittered<S> {
+    pub fn new(schedule: S, jitter: Jitter) -> Self {
+        let (window, seed, per_instant) = match jitter {
+            Jitter::Random(window) => (window, RandomState::new().build_hasher().finish(), true),
+            Jitter::Hashed { key, window } => (window, stable_hash(&key), false),
+        };
+        Self {
+            schedule,
+            window: window.num_seconds().max(0),
+            seed,
+            per_instant,
+        }
+    }
+    /// 原时间点推迟的偏移
+    pub fn offset(&self, datetime: NaiveDateTime) -> Duration {
+        if self.window <= 1 {
+            return Duration::zero();
+        }
+        let hash = match self.per_instant {
+            true => mix(self.seed ^ datetime.timestamp() as u64),
+            false => self.seed,
+        };
+        Duration::seconds((hash % self.window as u64) as i64)
+    }
+    fn jittered(&self, datetime: NaiveDateTime) -> NaiveDateTime {
+        datetime.add(self.offset(datetime))
+    }
+}
+
+impl<S: Schedule> Schedule for Jittered<S> {
+    /// 固定偏移时，平移原计划查找。随机抖动可能改变先后顺序，因此在所有可能晚于now的原时间点里取最早的：
+    /// 只看now往前window以内、直到推迟后的最早结果为止的原时间点，推迟到now后1秒时不必再看，
+    /// 耗时与一个window内的原时间点数成正比。与其他组合计划一样，最多往后找400年
+    fn next_with_time(&self, now: NaiveDateTime) -> Option<NaiveDateTime> {
+        let limit = horizon(now, true);
+        if !self.per_instant {
+            let offset = self.offset(now);
+            let datetime = self.schedule.next_with_time(now.sub(offset))?;
+            return (datetime <= limit).then(|| datetime.add(offset));
+        }
+        let earliest = now.checked_add_signed(Duration::seconds(1))?;
+        let mut best: Option<NaiveDateTime> = None;
+        let mut current = now.sub(Duration::seconds(self.window));
+        while let Some(datetime) = self.schedule.next_with_time(current) {
+            // 之后的原时间点推迟后不会早于best
+            if datetime > limit || best.is_some_and(|best| datetime >= best || best == earliest) {
+                break;
+            }
+            let jittered = self.jittered(datetime);
+            if jittered > now {
+                best = Some(best.map_or(jittered, |best| best.min(jittered)));
+            }
+            current = datetime;
+        }
+        best
+    }
+    fn prev_with_time(&self, now: NaiveDateTime) -> Option<NaiveDateTime> {
+        let limit = horizon(now, false);
+        if !self.per_instant {
+            let offset = self.offset(now);
+            let datetime = self.schedule.prev_with_time(now.sub(offset))?;
+            return (datetime >= limit).then(|| datetime.add(offset));
+        }
+        let latest = now.checked_sub_signed(Duration::seconds(1))?;
+        let mut best: Option<NaiveDateTime> = None;
+        let mut current = now;
+        while let Some(datetime) = self.schedule.prev_with_time(current) {
+            // 之前的原时间点推迟后不会晚于best
+            if datetime < limit
+                || best.is_some_and(|best| {
+                    datetime.add(Duration::seconds(self.window)) <= best || best == latest
+                })
+            {
+                break;
+            }
+            let jittered = self.jittered(datetime);
+            if jittered < now {
+                best = Some(best.map_or(jittered, |best| best.max(jittered)));
+            }
+            current = datetime;
+        }
+        best
+    }
+    /// 固定偏移时，整段平移原计划不符合的时间点；随机抖动打散了连续的时段，逐个时间点判断
+    fn next_unmatched(&self, now: NaiveDateTime) -> Option<NaiveDateTime> {
+        if !self.per_instant {
+            let offset = self.offset(now);
+            return Some(self.schedule.next_unmatched(now.sub(offset))?.add(offset));
+        }
+        let limit = horizon(now, true);
+        let mut current = now;
+        loop {
+            let next = current.checked_add_signed(Duration::seconds(1))?;
+            if self.next_with_time(current) != Some(next) {
+                return Some(next);
+            }
+            if next > limit {
+                return None;
+            }
+            current = next;
+        }
+    }
+    fn prev_unmatched(&self, now: NaiveDateTime) -> Option<NaiveDateTime> {
+        if !self.per_instant {
+            let offset = self.offset(now);
+            return Some(self.schedule.prev_unmatched(now.sub(offset))?.add(offset));
+        }
+        let limit = horizon(now, false);
+        let mut current = now;
+        loop {
+            let prev = current.checked_sub_signed(Duration::seconds(1))?;
+            if self.prev_with_time(current) != Some(prev) {
+                return Some(prev);
+            }
+            if prev < limit {
+                return None;
+            }
+            current = prev;
+        }
+    }
+    fn valid_range(&self) -> (Option<NaiveDateTime>, Option<NaiveDateTime>) {
+        let (start, end) = self.schedule.valid_range();
+        (start, end.map(|x| x.add(Duration::seconds(self.window))))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::conf::test::{check_unmatched, datetime};
+    use crate::*;
+    use chrono::{Duration, NaiveDateTime};
+
+    #[test]
+    fn test_jitter() -> anyhow::Result<()> {
+        let conf = || {
+            configure_monthday(MonthDays::default_all())
+                .build_with_hours(Hours::default_value(H0))
+                .build_with_minuter(Minuters::default_value(M0))
+                .build_with_second(Seconds::default_value(S0))
+        };
+        let window = Duration::minutes(10);
+        let now = datetime(2024, 1, 1, 0, 0, 0);
+
+        // 同一个key总是相同的偏移，推迟后now当天的时间点也晚于now
+        let hashed = conf().jitter(Jitter::Hashed {
+            key: "backup".to_string(),
+            window,
+        });
+        let offset = hashed.offset(now);
+        assert!(offset < window);
+        let same = conf().jitter(Jitter::Hashed {
+            key: "backup".to_string(),
+            window,
+        });
+        assert_eq!(same.offset(now), offset);
+        let times = hashed.datetimes(now..datetime(2024, 1, 4, 0, 0, 0))?;
+        assert_eq!(times.len(), 3);
+        for (index, time) in times.iter().enumerate() {
+            assert_eq!(*time, datetime(2024, 1, 1 + index as u32, 0, 0, 0) + offset);
+        }
+        assert_eq!(
+            hashed.prev_with_time(times[0]),
+            Some(datetime(2023, 12, 31, 0, 0, 0) + offset)
+        );
+
+        // 随机抖动：每个时间点的偏移都在窗口内，且前后一致
+        let random = conf().jitter(Jitter::Random(window));
+        let times = random.datetimes(now..datetime(2024, 2, 1, 0, 0, 0))?;
+        assert!(times.len() >= 30);
+        for time in times.iter() {
+            let base = time.date().and_hms(0, 0, 0);
+            assert!(*time >= base && *time < base + window);
+            assert_eq!(random.offset(base), *time - base);
+            assert!(random.matches(*time));
+            assert_eq!(
+                random.prev_with_time(*time + Duration::seconds(1)),
+                Some(*time)
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_hashed() -> anyhow::Result<()> {
+        // H(0-29)：同一个key总是同一分钟
+        let minuters = Minuters::hashed("backup", M0..=M29)?;
+        assert_eq!(minuters.len(), 1);
+        assert!(minuters.first().unwrap().as_data() <= 29);
+        assert_eq!(minuters, Minuters::hashed("backup", M0..=M29)?);
+        let all = (0..100)
+            .map(|x| Minuters::hashed(&format!("job-{}", x), ..))
+            .collect::<Result<Vec<_>, _>>()?;
+        // 不同的key分散到不同的分钟
+        assert!(
+            all.iter()
+                .map(|x| x.first())
+                .collect::<std::collections::HashSet<_>>()
+                .len()
+                > 30
+        );
+
+        // H/15：每15分钟，起点固定推迟15分钟以内
+        let minuters = Minuters::hashed_step("backup", .., 15)?;
+        assert_eq!(minuters.len(), 4);
+        let first = minuters.first().unwrap().as_data();
+        assert!(first < 15);
+        assert_eq!(
+            minuters.to_vec(),
+            vec![first, first + 15, first + 30, first + 45]
+        );
+        assert!(Hours::hashed_step("backup", .., 0).is_err());
+        assert!(Hours::hashed("backup", H5..H3).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_cron() -> anyhow::Result<()> {
+        let parse = |text| Minuters::parse_cron(text, "backup");
+        assert_eq!(parse("H")?, Minuters::hashed("backup", ..)?);
+        assert_eq!(parse("H(0-29)")?, Minuters::hashed("backup", M0..=M29)?);
+        assert_eq!(parse("H/15")?, Minuters::hashed_step("backup", .., 15)?);
+        assert_eq!(
+            parse("H(0-29)/10")?,
+            Minuters::hashed_step("backup", M0..=M29, 10)?
+        );
+        // 普通的cron写法
+        assert_eq!(parse("0,30")?, Minuters::default_array(&[M0, M30]));
+        assert_eq!(parse("*/15")?, Minuters::every(15)?);
+        assert_eq!(parse("5/20")?, Minuters::default_array(&[M5, M25, M45]));
+        assert_eq!(
+            parse("10-12, H(40-49)")?,
+            Minuters::default_range(M10..=M12)?.merge(&Minuters::hashed("backup", M40..=M49)?)
+        );
+        assert_eq!(Hours::parse_cron("H(9-17)", "backup")?.len(), 1);
+        // 不同的key分散到不同的值
+        let all = (0..100)
+            .map(|x| Minuters::parse_cron("H", &format!("job-{}", x)))
+            .collect::<Result<Vec<_>, _>>()?;
+        assert!(all.iter().collect::<std::collections::HashSet<_>>().len() > 30);
+
+        for text in ["H(0-29", "H5", "x", "1/y"] {
+            assert!(
+                matches!(parse(text), Err(Error::ParseText { .. })),
+                "{}",
+                text
+            );
+        }
+        assert!(matches!(parse("70"), Err(Error::OutOfRange { .. })));
+        assert!(matches!(parse("H/0"), Err(Error::InvalidStep { .. })));
+        assert!(matches!(parse("H(30-10)"), Err(Error::EmptyRange { .. })));
+        Ok(())
+    }
+
+    #[test]
+    fn test_jitter_horizon() {
+        let now = datetime(2024, 1, 1, 0, 0, 0);
+        let far = datetime(2500, 1, 1, 0, 0, 0);
+        let jittered = FixedTimes::default_array(&[now, far]).jitter(Jitter::Hashed {
+            key: "backup".to_string(),
+            window: Duration::minutes(10),
+        });
+        // 超过400年的时间点不再查找
+        assert_eq!(jittered.next_with_time(now + Duration::hours(1)), None);
+        assert_eq!(jittered.prev_with_time(far), None);
+        let offset = jittered.offset(now);
+        assert_eq!(
+            jittered.next_with_time(now - Duration::hours(1)),
+            Some(now + offset)
+        );
+        assert_eq!(
+            jittered.prev_with_time(datetime(2400, 1, 1, 0, 0, 0)),
+            Some(now + offset)
+        );
+    }
+
+    #[test]
+    fn test_hashed_lookups() {
+        // 记下原计划被查找的次数
+        struct Counted<S>(S, std::cell::Cell<usize>);
+        impl<S: Schedule> Schedule for Counted<S> {
+            fn next_with_time(&self, now: NaiveDateTime) -> Option<NaiveDateTime> {
+                self.1.set(self.1.get() + 1);
+                self.0.next_with_time(now)
+            }
+            fn prev_with_time(&self, now: NaiveDateTime) -> Option<NaiveDateTime> {
+                self.1.set(self.1.get() + 1);
+                self.0.prev_with_time(now)
+            }
+        }
+        // 每秒一次、推迟1小时以内：固定偏移时只查找一次原计划
+        let conf = configure_monthday(MonthDays::default_all())
+            .build_with_hours(Hours::default_all())
+            .build_with_minuter(Minuters::default_all())
+            .build_with_second(Seconds::default_all());
+        let jittered = Counted(conf, Default::default()).jitter(Jitter::Hashed {
+            key: "backup".to_string(),
+            window: Duration::hours(1),
+        });
+        let now = datetime(2024, 1, 1, 0, 0, 0);
+        assert_eq!(
+            jittered.next_with_time(now),
+            Some(now + Duration::seconds(1))
+        );
+        assert_eq!(
+            jittered.prev_with_time(now),
+            Some(now - Duration::seconds(1))
+        );
+        assert_eq!(jittered.schedule.1.get(), 2);
+    }
+
+    #[test]
+    fn test_unmatched() {
+        // 每天9点每秒一次，固定推迟时整段平移
+        let conf = || {
+            configure_monthday(MonthDays::default_all())
+                .build_with_hours(Hours::default_value(H9))
+                .build_with_minuter(Minuters::default_all())
+                .build_with_second(Seconds::default_all())
+        };
+        let window = Duration::seconds(30);
+        let hashed = conf().jitter(Jitter::Hashed {
+            key: "backup".to_string(),
+            window,
+        });
+        let random = conf().jitter(Jitter::Random(window));
+        for now in [
+            datetime(2022, 5, 6, 8, 59, 59),
+            datetime(2022, 5, 6, 9, 30, 0),
+            datetime(2022, 5, 6, 10, 0, 10),
+            datetime(2022, 5, 6, 12, 0, 0),
+        ] {
+            check_unmatched(&hashed, now, 2);
+            check_unmatched(&random, now, 2);
+        }
+    }
+}
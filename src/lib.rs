@@ -1,17 +1,53 @@
 #![allow(deprecated)]
 use crate::builder::DayConfBuilder;
-pub use conf::{Hours, Minuters, MonthDays, Seconds, TimerConf, WeekDays};
+use std::sync::Arc;
+pub use calendar::{DateListCalendar, HolidayCalendar, HolidayPolicy};
+pub use china::ChinaCalendar;
+pub use combinator::{Except, Intersect, Union};
+pub use compiled::CompiledTimer;
+pub use conflict::{Collision, ConflictDetector};
+pub use conf::{
+    Days, DaysMode, Hours, IsoWeeks, LunarMonths, Minuters, MonthDays, MonthWeeks, Seconds,
+    SolarTerms, TimerConf, WeekDays, YearDays,
+};
 pub use data::{
-    Hour, Hour::*, Minuter, Minuter::*, MonthDay, MonthDay::*, Second, Second::*, WeekDay,
-    WeekDay::*,
+    Hour, Hour::*, IsoWeek, LunarMonth, LunarMonth::*, Minuter, Minuter::*, MonthDay, MonthDay::*,
+    MonthWeek, Second, Second::*, SolarTerm, WeekDay, WeekDay::*, WeekStart,
 };
+pub use error::Error;
+pub use exclusion::{Excluded, Exclusion};
+pub use explain::{DayMatch, DaySource, Explanation, FieldMatch, SkipReason, Skipped};
+pub use fixed::FixedTimes;
+pub use jitter::{Jitter, Jittered};
+pub use limit::Limited;
+pub use lunar::{LeapMonth, LunarDate, LunarDays};
+pub use schedule::{Schedule, ScheduleIter};
 pub use traits::*;
+pub use window::Window;
 
 mod builder;
+mod calendar;
+mod china;
+mod combinator;
+mod compiled;
 mod compute;
 mod conf;
+mod conflict;
 mod data;
+mod error;
+mod exclusion;
+mod explain;
+mod fixed;
+mod hash;
+mod jitter;
+mod limit;
+mod lunar;
+mod ops;
+mod schedule;
+mod solar_term;
+mod stats;
 mod traits;
+mod window;
 
 pub fn configure_weekday(week_day: WeekDays) -> builder::DayConfBuilder {
     DayConfBuilder::from(week_day)
@@ -19,3 +55,19 @@ pub fn configure_weekday(week_day: WeekDays) -> builder::DayConfBuilder {
 pub fn configure_monthday(month_day: MonthDays) -> builder::DayConfBuilder {
     DayConfBuilder::from(month_day)
 }
+/// 按每年的第几天
+pub fn configure_yearday(year_day: YearDays) -> builder::DayConfBuilder {
+    DayConfBuilder::from(year_day)
+}
+/// 按节假日日历的每个工作日
+pub fn configure_workday(calendar: impl HolidayCalendar + 'static) -> builder::DayConfBuilder {
+    DayConfBuilder::default_work_days(Arc::new(calendar))
+}
+/// 按农历日期，如每年农历八月十五
+pub fn configure_lunar(lunar_days: LunarDays) -> builder::DayConfBuilder {
+    DayConfBuilder::from(lunar_days)
+}
+/// 按节气，如每年清明、冬至
+pub fn configure_solar_terms(solar_terms: SolarTerms) -> builder::DayConfBuilder {
+    DayConfBuilder::from(solar_terms)
+}
@@ -0,0 +1,243 @@
+use crate::error::{Error, Result};
+use crate::schedule::Schedule;
+use chrono::{Duration, Local, NaiveDateTime};
+use std::ops::{Add, Sub};
+
+/// 次数限制：以start为起点（包含），只触发前count次。如“前3个周一”
+#[derive(Debug, Clone)]
+pub struct Limited<S: Schedule> {
+    schedule: S,
+    start: NaiveDateTime,
+    count: usize,
+    // 第count次的时间点；None表示一次都不会触发
+    last: Option<NaiveDateTime>,
+}
+
+impl<S: Schedule> Limited<S> {
+    pub fn new(schedule: S, start: NaiveDateTime, count: usize) -> Self {
+        let last = if count == 0 {
+            None
+        } else {
+            schedule
+                .iter_from(start.sub(Duration::seconds(1)))
+                .take(count)
+                .last()
+        };
+        Self {
+            schedule,
+            start,
+            count,
+            last,
+        }
+    }
+    /// 按RRULE的COUNT构造，如`RRULE:FREQ=WEEKLY;BYDAY=MO;COUNT=3`。
+    /// 重复规则本身由schedule描述，这里只读取COUNT，其余部分忽略
+    pub fn from_rrule(schedule: S, start: NaiveDateTime, rrule: &str) -> Result<Self> {
+        let rule = rrule.trim();
+        let rule = match rule.split_once(':') {
+            Some((name, rule)) if name.eq_ignore_ascii_case("RRULE") => rule,
+            _ => rule,
+        };
+        let count = rule
+            .split(';')
+            .filter_map(|part| part.split_once('='))
+            .find(|(name, _)| name.trim().eq_ignore_ascii_case("COUNT"))
+            .ok_or_else(|| Error::parse_text(format!("COUNT not found in `{}`", rrule)))?
+            .1;
+        let count = count
+            .trim()
+            .parse()
+            .map_err(|_| Error::parse_text(format!("invalid COUNT `{}`", count)))?;
+        Ok(Self::new(schedule, start, count))
+    }
+    /// 次数限制对应的RRULE片段，如`COUNT=3`，可拼接到重复规则后
+    pub fn rrule_count(&self) -> String {
+        format!("COUNT={}", self.count)
+    }
+    /// 起点（包含）
+    pub fn start(&self) -> NaiveDateTime {
+        self.start
+    }
+    /// 总的触发次数上限
+    pub fn count(&self) -> usize {
+        self.count
+    }
+    /// 最后一次触发的时间点
+    pub fn last(&self) -> Option<NaiveDateTime> {
+        self.last
+    }
+    /// 以当前时间点为起点(不包含该时点)，剩余的触发次数
+    pub fn remaining(&self) -> usize {
+        self.remaining_with_time(Local::now().naive_local())
+    }
+    /// 以给定的时间点为起点(不包含该时点)，剩余的触发次数
+    pub fn remaining_with_time(&self, now: NaiveDateTime) -> usize {
+        self.iter_from(now).count()
+    }
+}
+
+impl<S: Schedule> Schedule for Limited<S> {
+    fn next_with_time(&self, now: NaiveDateTime) -> Option<NaiveDateTime> {
+        let last = self.last?;
+        let now = now.max(self.start.sub(Duration::seconds(1)));
+        self.schedule
+            .next_with_time(now)
+            .filter(|next| *next <= last)
+    }
+    fn prev_with_time(&self, now: NaiveDateTime) -> Option<NaiveDateTime> {
+        let last = self.last?;
+        let now = now.min(last.add(Duration::seconds(1)));
+        self.schedule
+            .prev_with_time(now)
+            .filter(|prev| *prev >= self.start)
+    }
+    fn matches(&self, datetime: NaiveDateTime) -> bool {
+        self.last.is_some_and(|last| self.start <= datetime && datetime <= last)
+            && self.schedule.matches(datetime)
+    }
+    /// 起点到最后一次之间与原计划相同，之外都不符合
+    fn next_unmatched(&self, now: NaiveDateTime) -> Option<NaiveDateTime> {
+        let next = now.checked_add_signed(Duration::seconds(1))?;
+        match self.last {
+            Some(last) if self.start <= next && next <= last => {
+                let after = last.add(Duration::seconds(1));
+                Some(self.schedule.next_unmatched(now).map_or(after, |x| x.min(after)))
+            }
+            _ => Some(next),
+        }
+    }
+    fn prev_unmatched(&self, now: NaiveDateTime) -> Option<NaiveDateTime> {
+        let prev = now.checked_sub_signed(Duration::seconds(1))?;
+        match self.last {
+            Some(last) if self.start <= prev && prev <= last => {
+                let before = self.start.sub(Duration::seconds(1));
+                Some(self.schedule.prev_unmatched(now).map_or(before, |x| x.max(before)))
+            }
+            _ => Some(prev),
+        }
+    }
+    fn valid_range(&self) -> (Option<NaiveDateTime>, Option<NaiveDateTime>) {
+        let (start, end) = self.schedule.valid_range();
+        let end = match (end, self.last) {
+            (Some(end), Some(last)) => Some(end.min(last)),
+            (end, last) => end.or(last),
+        };
+        (start.max(Some(self.start)), end)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::conf::test::{check_unmatched, datetime};
+    use crate::*;
+
+    #[test]
+    fn test_limited() -> anyhow::Result<()> {
+        // 从2022-08-03开始的前3个周一
+        let conf = configure_weekday(WeekDays::default_value(W1))
+            .build_with_hours(Hours::default_value(H10))
+            .build_with_minuter(Minuters::default_value(M0))
+            .build_with_second(Seconds::default_value(S0));
+        let limited = conf.limit(datetime(2022, 8, 3, 0, 0, 0), 3);
+        assert_eq!(limited.last(), Some(datetime(2022, 8, 22, 10, 0, 0)));
+        assert_eq!(
+            limited.datetimes(..)?,
+            vec![
+                datetime(2022, 8, 8, 10, 0, 0),
+                datetime(2022, 8, 15, 10, 0, 0),
+                datetime(2022, 8, 22, 10, 0, 0),
+            ]
+        );
+        assert_eq!(limited.remaining_with_time(datetime(2022, 8, 1, 0, 0, 0)), 3);
+        assert_eq!(limited.remaining_with_time(datetime(2022, 8, 8, 10, 0, 0)), 2);
+        assert_eq!(limited.remaining_with_time(datetime(2022, 8, 22, 10, 0, 0)), 0);
+        assert_eq!(limited.next_with_time(datetime(2022, 8, 22, 10, 0, 0)), None);
+        assert_eq!(
+            limited.prev_with_time(datetime(2023, 1, 1, 0, 0, 0)),
+            Some(datetime(2022, 8, 22, 10, 0, 0))
+        );
+        assert_eq!(limited.prev_with_time(datetime(2022, 8, 8, 10, 0, 0)), None);
+
+        // 起点本身符合时计入次数
+        let limited = FixedTimes::default_array(&[
+            datetime(2022, 8, 1, 0, 0, 0),
+            datetime(2022, 8, 2, 0, 0, 0),
+        ])
+        .limit(datetime(2022, 8, 1, 0, 0, 0), 1);
+        assert_eq!(limited.datetimes(..)?, vec![datetime(2022, 8, 1, 0, 0, 0)]);
+
+        let never = FixedTimes::once(datetime(2022, 8, 1, 0, 0, 0))
+            .limit(datetime(2022, 8, 1, 0, 0, 0), 0);
+        assert_eq!(never.next_with_time(datetime(2022, 7, 1, 0, 0, 0)), None);
+        assert_eq!(never.remaining_with_time(datetime(2022, 7, 1, 0, 0, 0)), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_remaining_fewer_than_count() -> anyhow::Result<()> {
+        // 实际能触发的次数少于count时，以实际次数为准
+        let limited = FixedTimes::default_array(&[
+            datetime(2022, 8, 1, 0, 0, 0),
+            datetime(2022, 8, 2, 0, 0, 0),
+        ])
+        .limit(datetime(2022, 8, 1, 0, 0, 0), 5);
+        assert_eq!(limited.last(), Some(datetime(2022, 8, 2, 0, 0, 0)));
+        assert_eq!(limited.remaining_with_time(datetime(2022, 7, 1, 0, 0, 0)), 2);
+        assert_eq!(limited.remaining_with_time(datetime(2022, 8, 1, 0, 0, 0)), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_rrule_count() -> anyhow::Result<()> {
+        let conf = configure_weekday(WeekDays::default_value(W1))
+            .build_with_hours(Hours::default_value(H10))
+            .build_with_minuter(Minuters::default_value(M0))
+            .build_with_second(Seconds::default_value(S0));
+        let start = datetime(2022, 8, 3, 0, 0, 0);
+        let limited =
+            Limited::from_rrule(conf.clone(), start, "RRULE:FREQ=WEEKLY;BYDAY=MO;COUNT=3")?;
+        assert_eq!(limited.count(), 3);
+        assert_eq!(limited.last(), Some(datetime(2022, 8, 22, 10, 0, 0)));
+        assert_eq!(limited.rrule_count(), "COUNT=3");
+        // 往返
+        let again = Limited::from_rrule(conf.clone(), start, &limited.rrule_count())?;
+        assert_eq!(again.count(), limited.count());
+        assert_eq!(again.last(), limited.last());
+        assert_eq!(
+            Limited::from_rrule(conf.clone(), start, "freq=weekly; count=1")?.count(),
+            1
+        );
+
+        assert!(matches!(
+            Limited::from_rrule(conf.clone(), start, "RRULE:FREQ=WEEKLY;BYDAY=MO"),
+            Err(Error::ParseText { .. })
+        ));
+        assert!(matches!(
+            Limited::from_rrule(conf, start, "RRULE:FREQ=WEEKLY;COUNT=-1"),
+            Err(Error::ParseText { .. })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_unmatched() -> anyhow::Result<()> {
+        // 每天9点每秒一次，从9:30起4000次：到次日9:36:39为止
+        let conf = configure_monthday(MonthDays::default_all())
+            .build_with_hours(Hours::default_value(H9))
+            .build_with_minuter(Minuters::default_all())
+            .build_with_second(Seconds::default_all())
+            .limit(datetime(2022, 5, 6, 9, 30, 0), 4000);
+        assert_eq!(conf.last(), Some(datetime(2022, 5, 7, 9, 36, 39)));
+        for now in [
+            datetime(2022, 5, 6, 9, 0, 0),
+            datetime(2022, 5, 6, 9, 29, 59),
+            datetime(2022, 5, 6, 9, 45, 0),
+            datetime(2022, 5, 7, 9, 0, 0),
+            datetime(2022, 5, 7, 9, 36, 39),
+            datetime(2022, 5, 7, 9, 40, 0),
+        ] {
+            check_unmatched(&conf, now, 3);
+        }
+        Ok(())
+    }
+}
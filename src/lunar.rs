@@ -0,0 +1,448 @@
+use crate::compute;
+use crate::conf::{LunarMonths, MonthDays};
+use crate::data::{LunarMonth, MonthDay};
+use crate::traits::{ConfigOperator, FromData};
+use chrono::{Datelike, NaiveDate};
+use std::sync::OnceLock;
+
+/// 支持换算的农历年份范围
+const FIRST_YEAR: i32 = 1900;
+const LAST_YEAR: i32 = 2100;
+
+/// 1900-2100年的农历数据，每年一项：
+/// - 第0-3位：闰月月份，0表示没有闰月
+/// - 第4-15位：正月到腊月的大小，第15位为正月，1为大月（30天），0为小月（29天）
+/// - 第16位：闰月的大小
+const LUNAR_INFO: [u32; 201] = [
+    // 1900
+    0x04bd8, 0x04ae0, 0x0a570, 0x054d5, 0x0d260, 0x0d950, 0x16554, 0x056a0, 0x09ad0, 0x055d2,
+    // 1910
+    0x04ae0, 0x0a5b6, 0x0a4d0, 0x0d250, 0x1d255, 0x0b540, 0x0d6a0, 0x0ada2, 0x095b0, 0x14977,
+    // 1920
+    0x04970, 0x0a4b0, 0x0b4b5, 0x06a50, 0x06d40, 0x1ab54, 0x02b60, 0x09570, 0x052f2, 0x04970,
+    // 1930
+    0x06566, 0x0d4a0, 0x0ea50, 0x16a95, 0x05ad0, 0x02b60, 0x186e3, 0x092e0, 0x1c8d7, 0x0c950,
+    // 1940
+    0x0d4a0, 0x1d8a6, 0x0b550, 0x056a0, 0x1a5b4, 0x025d0, 0x092d0, 0x0d2b2, 0x0a950, 0x0b557,
+    // 1950
+    0x06ca0, 0x0b550, 0x15355, 0x04da0, 0x0a5b0, 0x14573, 0x052b0, 0x0a9a8, 0x0e950, 0x06aa0,
+    // 1960
+    0x0aea6, 0x0ab50, 0x04b60, 0x0aae4, 0x0a570, 0x05260, 0x0f263, 0x0d950, 0x05b57, 0x056a0,
+    // 1970
+    0x096d0, 0x04dd5, 0x04ad0, 0x0a4d0, 0x0d4d4, 0x0d250, 0x0d558, 0x0b540, 0x0b6a0, 0x195a6,
+    // 1980
+    0x095b0, 0x049b0, 0x0a974, 0x0a4b0, 0x0b27a, 0x06a50, 0x06d40, 0x0af46, 0x0ab60, 0x09570,
+    // 1990
+    0x04af5, 0x04970, 0x064b0, 0x074a3, 0x0ea50, 0x06b58, 0x05ac0, 0x0ab60, 0x096d5, 0x092e0,
+    // 2000
+    0x0c960, 0x0d954, 0x0d4a0, 0x0da50, 0x07552, 0x056a0, 0x0abb7, 0x025d0, 0x092d0, 0x0cab5,
+    // 2010
+    0x0a950, 0x0b4a0, 0x0baa4, 0x0ad50, 0x055d9, 0x04ba0, 0x0a5b0, 0x15176, 0x052b0, 0x0a930,
+    // 2020
+    0x07954, 0x06aa0, 0x0ad50, 0x05b52, 0x04b60, 0x0a6e6, 0x0a4e0, 0x0d260, 0x0ea65, 0x0d530,
+    // 2030
+    0x05aa0, 0x076a3, 0x096d0, 0x04afb, 0x04ad0, 0x0a4d0, 0x1d0b6, 0x0d250, 0x0d520, 0x0dd45,
+    // 2040
+    0x0b5a0, 0x056d0, 0x055b2, 0x049b0, 0x0a577, 0x0a4b0, 0x0aa50, 0x1b255, 0x06d20, 0x0ada0,
+    // 2050
+    0x14b63, 0x09370, 0x049f8, 0x04970, 0x064b0, 0x168a6, 0x0ea50, 0x06b20, 0x1a6c4, 0x0aae0,
+    // 2060
+    0x092e0, 0x0d2e3, 0x0c960, 0x0d557, 0x0d4a0, 0x0da50, 0x05d55, 0x056a0, 0x0a6d0, 0x055d4,
+    // 2070
+    0x052d0, 0x0a9b8, 0x0a950, 0x0b4a0, 0x0b6a6, 0x0ad50, 0x055a0, 0x0aba4, 0x0a5b0, 0x052b0,
+    // 2080
+    0x0b273, 0x06930, 0x07337, 0x06aa0, 0x0ad50, 0x14b55, 0x04b60, 0x0a570, 0x054e4, 0x0d160,
+    // 2090
+    0x0e968, 0x0d520, 0x0daa0, 0x16aa6, 0x056d0, 0x04ae0, 0x0a9d4, 0x0a2d0, 0x0d150, 0x0f252,
+    // 2100
+    0x0d520,
+];
+
+/// 农历1900年正月初一对应的公历日期
+fn base_date() -> NaiveDate {
+    NaiveDate::from_ymd(1900, 1, 31)
+}
+
+/// 农历year年的闰月月份
+fn leap_month(year: i32) -> Option<u32> {
+    match LUNAR_INFO[(year - FIRST_YEAR) as usize] & 0xf {
+        0 => None,
+        month => Some(month),
+    }
+}
+
+/// 农历year年month月（leap为闰月）的天数
+fn month_len(year: i32, month: u32, leap: bool) -> u32 {
+    let info = LUNAR_INFO[(year - FIRST_YEAR) as usize];
+    let big = if leap {
+        info & 0x10000
+    } else {
+        info & (0x10000 >> month)
+    };
+    if big > 0 {
+        30
+    } else {
+        29
+    }
+}
+
+/// 农历year年的天数
+fn year_len(year: i32) -> i64 {
+    let days: u32 = (1..=12).map(|month| month_len(year, month, false)).sum();
+    let leap = leap_month(year).map_or(0, |month| month_len(year, month, true));
+    (days + leap) as i64
+}
+
+/// 各农历年正月初一距[`base_date`]的天数，最后一项为2100年之后的一天。首次使用时计算并缓存，
+/// 公历与农历互相换算时据此直接定位到农历年，不必每次从1900年逐年累加
+fn year_offsets() -> &'static [i64] {
+    static OFFSETS: OnceLock<Vec<i64>> = OnceLock::new();
+    OFFSETS.get_or_init(|| {
+        let mut offsets = vec![0];
+        for year in FIRST_YEAR..=LAST_YEAR {
+            offsets.push(offsets[offsets.len() - 1] + year_len(year));
+        }
+        offsets
+    })
+}
+
+/// 农历日期可换算的公历年份范围：农历1900年正月初一在公历1900年，农历2100年腊月在公历2101年
+pub(crate) fn solar_years() -> (i32, i32) {
+    (FIRST_YEAR, LAST_YEAR + 1)
+}
+
+/// 农历日期
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct LunarDate {
+    year: i32,
+    month: u32,
+    day: u32,
+    leap: bool,
+}
+
+impl LunarDate {
+    /// 农历year年month月day日，leap为是否闰月；日期不存在或超出1900-2100年时返回None
+    pub fn new(year: i32, month: u32, day: u32, leap: bool) -> Option<Self> {
+        if !(FIRST_YEAR..=LAST_YEAR).contains(&year) || !(1..=12).contains(&month) || day == 0 {
+            return None;
+        }
+        if leap && leap_month(year) != Some(month) {
+            return None;
+        }
+        (day <= month_len(year, month, leap)).then_some(Self {
+            year,
+            month,
+            day,
+            leap,
+        })
+    }
+    /// 公历日期换算为农历日期；超出1900-2100年时返回None
+    pub fn from_solar(date: NaiveDate) -> Option<Self> {
+        let offset = (date - base_date()).num_days();
+        let offsets = year_offsets();
+        if offset < 0 || offset >= offsets[offsets.len() - 1] {
+            return None;
+        }
+        let index = offsets.partition_point(|x| *x <= offset) - 1;
+        let year = FIRST_YEAR + index as i32;
+        let mut offset = offset - offsets[index];
+        let leap = leap_month(year);
+        for month in 1..=12 {
+            for is_leap in [false, true] {
+                if is_leap && leap != Some(month) {
+                    continue;
+                }
+                let len = month_len(year, month, is_leap) as i64;
+                if offset < len {
+                    return Some(Self {
+                        year,
+                        month,
+                        day: offset as u32 + 1,
+                        leap: is_leap,
+                    });
+                }
+                offset -= len;
+            }
+        }
+        unreachable!("bug!")
+    }
+    /// 对应的公历日期
+    pub fn to_solar(&self) -> NaiveDate {
+        let mut days = year_offsets()[(self.year - FIRST_YEAR) as usize];
+        let leap = leap_month(self.year);
+        for month in 1..=self.month {
+            if month < self.month || self.leap {
+                days += month_len(self.year, month, false) as i64;
+            }
+            if month < self.month && leap == Some(month) {
+                days += month_len(self.year, month, true) as i64;
+            }
+        }
+        base_date() + chrono::Duration::days(days + self.day as i64 - 1)
+    }
+    /// 下一天；超出2100年时返回None
+    pub fn succ(&self) -> Option<Self> {
+        if self.day < month_len(self.year, self.month, self.leap) {
+            return Some(Self {
+                day: self.day + 1,
+                ..*self
+            });
+        }
+        if !self.leap && leap_month(self.year) == Some(self.month) {
+            return Some(Self {
+                day: 1,
+                leap: true,
+                ..*self
+            });
+        }
+        if self.month < 12 {
+            Self::new(self.year, self.month + 1, 1, false)
+        } else {
+            Self::new(self.year + 1, 1, 1, false)
+        }
+    }
+    pub fn year(&self) -> i32 {
+        self.year
+    }
+    pub fn month(&self) -> u32 {
+        self.month
+    }
+    pub fn day(&self) -> u32 {
+        self.day
+    }
+    /// 是否为闰月
+    pub fn is_leap(&self) -> bool {
+        self.leap
+    }
+}
+
+/// 闰月的处理方式
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
+pub enum LeapMonth {
+    /// 只选中正常月份，不选中闰月。节日一般按此处理
+    #[default]
+    Exclude,
+    /// 正常月份及其闰月都选中
+    Include,
+    /// 只选中闰月
+    Only,
+}
+
+/// 农历日期配置：选中的农历月份里选中的日子，如农历八月十五。
+/// 日子只有1-30，当月没有30日（小月）时不触发
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct LunarDays {
+    months: LunarMonths,
+    days: MonthDays,
+    leap: LeapMonth,
+}
+
+impl LunarDays {
+    pub fn new(months: LunarMonths, days: MonthDays) -> Self {
+        Self {
+            months,
+            days,
+            leap: LeapMonth::default(),
+        }
+    }
+    /// 农历month月day日，如春节、中秋
+    pub fn default_value(month: LunarMonth, day: MonthDay) -> Self {
+        Self::new(
+            LunarMonths::default_value(month),
+            MonthDays::default_value(day),
+        )
+    }
+    /// 设置闰月的处理方式，默认不选中闰月
+    pub fn leap(mut self, leap: LeapMonth) -> Self {
+        self.leap = leap;
+        self
+    }
+    fn matches(&self, date: &LunarDate) -> bool {
+        let leap = match self.leap {
+            LeapMonth::Exclude => !date.leap,
+            LeapMonth::Include => true,
+            LeapMonth::Only => date.leap,
+        };
+        leap && self
+            .months
+            .contain(LunarMonth::from_data(date.month as u64))
+            && self.days.contain(MonthDay::from_data(date.day as u64))
+    }
+    /// 给定的公历日期是否被选中
+    pub(crate) fn contain(&self, date: NaiveDate) -> bool {
+        LunarDate::from_solar(date).is_some_and(|x| self.matches(&x))
+    }
+    /// 给定的公历月份里被选中的日期
+    pub(crate) fn month_days(&self, year: i32, month: u32) -> MonthDays {
+        let mut month_days = MonthDays::_default();
+        // 1900年1月只有31号在表的范围内
+        let mut date = NaiveDate::from_ymd(year, month, 1).max(base_date());
+        let end = NaiveDate::from_ymd(year, month, compute::month_len(year, month));
+        let mut lunar = LunarDate::from_solar(date);
+        while date <= end {
+            let Some(current) = lunar else {
+                break;
+            };
+            if self.matches(&current) {
+                month_days = month_days.add(MonthDay::from_data(date.day() as u64));
+            }
+            date = date.succ();
+            lunar = current.succ();
+        }
+        month_days
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{LeapMonth, LunarDate, LunarDays};
+    use crate::conf::test::datetime;
+    use crate::*;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_lunar_date() {
+        // 春节
+        for (year, month, day) in [
+            (2000, 2, 5),
+            (2023, 1, 22),
+            (2024, 2, 10),
+            (2025, 1, 29),
+            (2026, 2, 17),
+        ] {
+            let date = NaiveDate::from_ymd(year, month, day);
+            assert_eq!(
+                LunarDate::from_solar(date),
+                LunarDate::new(year, 1, 1, false)
+            );
+            assert_eq!(LunarDate::new(year, 1, 1, false).unwrap().to_solar(), date);
+        }
+        // 2023年闰二月、2025年闰六月
+        let leap = LunarDate::from_solar(NaiveDate::from_ymd(2023, 3, 22)).unwrap();
+        assert_eq!((leap.month(), leap.day(), leap.is_leap()), (2, 1, true));
+        assert_eq!(
+            LunarDate::new(2025, 6, 15, true).unwrap().to_solar(),
+            NaiveDate::from_ymd(2025, 8, 8)
+        );
+        assert_eq!(LunarDate::new(2024, 6, 1, true), None);
+        // 2024年腊月只有29天
+        assert_eq!(LunarDate::new(2024, 12, 30, false), None);
+        assert_eq!(
+            LunarDate::new(2024, 12, 29, false).unwrap().succ(),
+            LunarDate::new(2025, 1, 1, false)
+        );
+        assert_eq!(
+            LunarDate::from_solar(NaiveDate::from_ymd(1900, 1, 30)),
+            None
+        );
+        assert_eq!(
+            LunarDate::from_solar(NaiveDate::from_ymd(2101, 1, 29)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_table_bounds() -> anyhow::Result<()> {
+        // 表的两端：农历1900年正月初一、2100年腊月的最后一天
+        let first = NaiveDate::from_ymd(1900, 1, 31);
+        assert_eq!(
+            LunarDate::from_solar(first),
+            LunarDate::new(1900, 1, 1, false)
+        );
+        let last = LunarDate::new(2100, 12, 29, false).unwrap();
+        assert_eq!(LunarDate::from_solar(last.to_solar()), Some(last));
+        assert_eq!(last.succ(), None);
+        assert_eq!(LunarDate::from_solar(last.to_solar().succ()), None);
+        // 逐日换算与逐日推算一致
+        let mut date = first;
+        let mut lunar = LunarDate::from_solar(first);
+        while let Some(current) = lunar {
+            assert_eq!(LunarDate::from_solar(date), Some(current));
+            assert_eq!(current.to_solar(), date);
+            date = date.succ();
+            lunar = current.succ();
+        }
+        assert_eq!(date, last.to_solar().succ());
+
+        // 超出表的范围时不再触发，也不会panic
+        let conf = configure_lunar(LunarDays::default_value(L1, D1))
+            .build_with_hours(Hours::default_value(H0))
+            .build_with_minuter(Minuters::default_value(M0))
+            .build_with_second(Seconds::default_value(S0));
+        assert_eq!(
+            Schedule::next_with_time(&conf, datetime(2100, 3, 1, 0, 0, 0)),
+            None
+        );
+        assert_eq!(
+            Schedule::next_with_time(&conf, datetime(2101, 3, 1, 0, 0, 0)),
+            None
+        );
+        assert_eq!(
+            Schedule::prev_with_time(&conf, datetime(1900, 1, 1, 0, 0, 0)),
+            None
+        );
+        assert_eq!(
+            Schedule::prev_with_time(&conf, datetime(1900, 2, 1, 0, 0, 0)),
+            Some(datetime(1900, 1, 31, 0, 0, 0))
+        );
+        assert_eq!(
+            Schedule::prev_with_time(&conf, datetime(2200, 1, 1, 0, 0, 0)),
+            Some(
+                LunarDate::new(2100, 1, 1, false)
+                    .unwrap()
+                    .to_solar()
+                    .and_hms(0, 0, 0)
+            )
+        );
+        assert_eq!(
+            conf.datetimes(datetime(1899, 1, 1, 0, 0, 0)..datetime(1900, 2, 1, 0, 0, 0))?,
+            vec![datetime(1900, 1, 31, 0, 0, 0)]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_lunar_days() -> anyhow::Result<()> {
+        // 每年中秋10点
+        let conf = configure_lunar(LunarDays::default_value(L8, D15))
+            .build_with_hours(Hours::default_value(H10))
+            .build_with_minuter(Minuters::default_value(M0))
+            .build_with_second(Seconds::default_value(S0));
+        assert_eq!(
+            conf.datetimes(datetime(2023, 1, 1, 0, 0, 0)..datetime(2027, 1, 1, 0, 0, 0))?,
+            vec![
+                datetime(2023, 9, 29, 10, 0, 0),
+                datetime(2024, 9, 17, 10, 0, 0),
+                datetime(2025, 10, 6, 10, 0, 0),
+                datetime(2026, 9, 25, 10, 0, 0),
+            ]
+        );
+        assert_eq!(
+            conf.prev_with_time(datetime(2025, 10, 6, 10, 0, 0)),
+            datetime(2024, 9, 17, 10, 0, 0)
+        );
+
+        // 2025年六月十五及闰六月十五
+        let days = LunarDays::default_value(L6, D15);
+        let range = datetime(2025, 1, 1, 0, 0, 0)..datetime(2026, 1, 1, 0, 0, 0);
+        let build = |days: LunarDays| {
+            configure_lunar(days)
+                .build_with_hours(Hours::default_value(H0))
+                .build_with_minuter(Minuters::default_value(M0))
+                .build_with_second(Seconds::default_value(S0))
+        };
+        assert_eq!(
+            build(days.clone()).datetimes(range.clone())?,
+            vec![datetime(2025, 7, 9, 0, 0, 0)]
+        );
+        assert_eq!(
+            build(days.clone().leap(LeapMonth::Include)).datetimes(range.clone())?,
+            vec![datetime(2025, 7, 9, 0, 0, 0), datetime(2025, 8, 8, 0, 0, 0)]
+        );
+        assert_eq!(
+            build(days.leap(LeapMonth::Only)).datetimes(range)?,
+            vec![datetime(2025, 8, 8, 0, 0, 0)]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,237 @@
+use crate::conf::{
+    Hours, IsoWeeks, LunarMonths, Minuters, MonthDays, MonthWeeks, Seconds, SolarTerms, WeekDays,
+};
+use crate::data::{
+    Hour, IsoWeek, LunarMonth, Minuter, MonthDay, MonthWeek, Second, SolarTerm, WeekDay,
+};
+use crate::error::Error;
+use crate::traits::{AsBizData, ConfigIter, ConfigOperator};
+use std::ops::{
+    BitAnd, BitOr, BitXor, Not, Range, RangeFrom, RangeInclusive, RangeTo, RangeToInclusive, Sub,
+};
+
+/// 配置项的集合运算：|并集、&交集、-差集、^对称差、!补集（MIN..=MAX内），
+/// 左侧可以是配置项或单个值，右侧可以是配置项、单个值或不会为空的区间，如`H9 | H12 | (H14..)`；
+/// 及从单个值、值的区间、值的迭代器生成配置项。
+/// `a..b`、`a..=b`、`..b`可能为空或端点无效，只能用TryFrom转换，如`Hours::try_from(H14..=H18)?`
+macro_rules! impl_set_ops {
+    ($($conf:ident: $data:ident),*) => {
+        $(
+            impl From<$data> for $conf {
+                fn from(val: $data) -> Self {
+                    $conf::default_value(val)
+                }
+            }
+            impl_set_ops!(@range $conf, $data, RangeFrom, RangeToInclusive);
+            impl_set_ops!(@try_range $conf, $data, Range, RangeInclusive, RangeTo);
+            impl_set_ops!(@op $conf, $data, BitOr, bitor, |a, b| a | b);
+            impl_set_ops!(@op $conf, $data, BitAnd, bitand, |a, b| a & b);
+            impl_set_ops!(@op $conf, $data, Sub, sub, |a, b| a & !b);
+            impl_set_ops!(@op $conf, $data, BitXor, bitxor, |a, b| a ^ b);
+            impl Not for $conf {
+                type Output = $conf;
+                fn not(self) -> Self::Output {
+                    let mut res = $conf::_default();
+                    res._val_mut($conf::DEFAULT_MAX & !self._val());
+                    res
+                }
+            }
+            impl FromIterator<$data> for $conf {
+                fn from_iter<I: IntoIterator<Item = $data>>(iter: I) -> Self {
+                    let mut res = $conf::_default();
+                    res.extend(iter);
+                    res
+                }
+            }
+            impl Extend<$data> for $conf {
+                fn extend<I: IntoIterator<Item = $data>>(&mut self, iter: I) {
+                    let val = iter.into_iter().fold(self._val(), |val, x| val | 1 << x.as_data());
+                    self._val_mut(val);
+                }
+            }
+            impl<'a> IntoIterator for &'a $conf {
+                type Item = $data;
+                type IntoIter = ConfigIter<$data>;
+                fn into_iter(self) -> Self::IntoIter {
+                    self.iter()
+                }
+            }
+            impl Not for $data {
+                type Output = $conf;
+                fn not(self) -> Self::Output {
+                    !$conf::from(self)
+                }
+            }
+        )*
+    };
+    // 起点或终点不限的区间不会为空
+    (@range $conf:ident, $data:ident, $($range:ident),*) => {
+        $(
+            impl From<$range<$data>> for $conf {
+                fn from(range: $range<$data>) -> Self {
+                    $conf::default_range(range).expect("a half-open range is never empty")
+                }
+            }
+        )*
+    };
+    // 空区间、终点不包含最小值的区间返回错误
+    (@try_range $conf:ident, $data:ident, $($range:ident),*) => {
+        $(
+            impl TryFrom<$range<$data>> for $conf {
+                type Error = Error;
+                fn try_from(range: $range<$data>) -> Result<Self, Self::Error> {
+                    $conf::default_range(range)
+                }
+            }
+        )*
+    };
+    (@op $conf:ident, $data:ident, $op:ident, $method:ident, |$a:ident, $b:ident| $expr:expr) => {
+        impl<T: Into<$conf>> $op<T> for $conf {
+            type Output = $conf;
+            fn $method(self, rhs: T) -> Self::Output {
+                let ($a, $b) = (self._val(), rhs.into()._val());
+                let mut res = $conf::_default();
+                res._val_mut($expr);
+                res
+            }
+        }
+        impl<T: Into<$conf>> $op<T> for $data {
+            type Output = $conf;
+            fn $method(self, rhs: T) -> Self::Output {
+                $op::$method($conf::from(self), rhs)
+            }
+        }
+    };
+}
+
+impl_set_ops!(
+    Hours: Hour,
+    Minuters: Minuter,
+    Seconds: Second,
+    MonthDays: MonthDay,
+    WeekDays: WeekDay,
+    LunarMonths: LunarMonth,
+    SolarTerms: SolarTerm,
+    MonthWeeks: MonthWeek,
+    IsoWeeks: IsoWeek
+);
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use anyhow::Result;
+
+    #[test]
+    fn test_set_ops() -> Result<()> {
+        let hours = H9 | H12 | Hours::try_from(H14..=H18)?;
+        assert_eq!(hours.to_vec(), vec![9, 12, 14, 15, 16, 17, 18]);
+        assert_eq!(
+            (hours.clone() & (H10..)).to_vec(),
+            vec![12, 14, 15, 16, 17, 18]
+        );
+        assert_eq!(
+            (hours.clone() - Hours::try_from(H12..H16)?).to_vec(),
+            vec![9, 16, 17, 18]
+        );
+        assert_eq!(
+            (Hours::try_from(H8..=H10)? ^ hours.clone()).to_vec(),
+            vec![8, 10, 12, 14, 15, 16, 17, 18]
+        );
+
+        // 除12点外的所有小时
+        let hours = !H12;
+        assert_eq!(hours.to_vec().len(), 23);
+        assert!(!hours.contain(H12));
+        assert!((!hours).contain(H12));
+
+        // 补集只在MIN..=MAX内
+        assert_eq!((!WeekDays::default_all()).to_vec(), Vec::<u64>::new());
+        assert_eq!((!(W6 | W7)).to_vec(), vec![1, 2, 3, 4, 5]);
+        assert_eq!((!MonthDays::try_from(D1..=D30)?).to_vec(), vec![31]);
+        assert_eq!(
+            (!Seconds::every(2)?).to_vec(),
+            Seconds::default_range_step(S1.., 2)?.to_vec()
+        );
+        Ok(())
+    }
+    #[test]
+    fn test_range_conversion() {
+        // 可能为空的区间只能用TryFrom转换，空区间、无效端点返回错误而不是啥都不选
+        assert!(matches!(
+            Hours::try_from(H5..H3),
+            Err(Error::EmptyRange { field: "hour", .. })
+        ));
+        assert!(matches!(
+            Hours::try_from(H5..=H3),
+            Err(Error::EmptyRange { field: "hour", .. })
+        ));
+        assert!(matches!(
+            Hours::try_from(H5..H5),
+            Err(Error::EmptyRange { .. })
+        ));
+        assert!(matches!(
+            Hours::try_from(..H0),
+            Err(Error::InvalidBound { field: "hour", .. })
+        ));
+        assert!(matches!(
+            MonthDays::try_from(..D1),
+            Err(Error::InvalidBound { .. })
+        ));
+        assert_eq!(Hours::try_from(H5..=H5).unwrap().to_vec(), vec![5]);
+        assert_eq!(Hours::try_from(..H2).unwrap().to_vec(), vec![0, 1]);
+        // 起点或终点不限的区间不会为空，可以直接参与运算
+        assert_eq!(Hours::from(H23..).to_vec(), vec![23]);
+        assert_eq!(Hours::from(..=H0).to_vec(), vec![0]);
+        assert_eq!((WeekDays::from(W6..) | W1).to_vec(), vec![1, 6, 7]);
+        assert_eq!((H1 | (..=H2)).to_vec(), vec![0, 1, 2]);
+    }
+    #[test]
+    fn test_data_ops() {
+        // 左侧为单个值时，各运算都与先转换为配置项一致
+        assert_eq!(H9 & H9, Hours::from(H9));
+        assert!((H9 & H10).is_empty());
+        assert_eq!((H9 & (H5..)).to_vec(), vec![9]);
+        assert!((H9 - (..=H10)).is_empty());
+        assert_eq!(H9 - H10, Hours::from(H9));
+        assert_eq!((W1 ^ (W1 | W2)).to_vec(), vec![2]);
+        assert_eq!((D31 ^ D31).len(), 0);
+        for rhs in [
+            Hours::default(),
+            Hours::from(H9),
+            Hours::from(H8..),
+            Hours::default_all(),
+        ] {
+            assert_eq!(H9 | rhs.clone(), Hours::from(H9) | rhs.clone());
+            assert_eq!(H9 & rhs.clone(), Hours::from(H9) & rhs.clone());
+            assert_eq!(H9 - rhs.clone(), Hours::from(H9) - rhs.clone());
+            assert_eq!(H9 ^ rhs.clone(), Hours::from(H9) ^ rhs);
+        }
+    }
+    #[test]
+    fn test_iter() {
+        use std::collections::HashSet;
+        let hours = H9 | H12 | Hours::try_from(H14..=H16).unwrap();
+        assert_eq!(
+            hours.iter().collect::<Vec<_>>(),
+            vec![H9, H12, H14, H15, H16]
+        );
+        assert_eq!(hours.iter().next_back(), Some(H16));
+        assert_eq!(hours.iter().len(), 5);
+        assert_eq!(hours.len(), 5);
+        assert_eq!(hours.first(), Some(H9));
+        assert_eq!(hours.last(), Some(H16));
+        assert_eq!(Hours::default().first(), None);
+        assert!(Hours::default().is_empty());
+        assert_eq!(Minuters::default_all().last(), Some(M59));
+        assert_eq!(MonthDays::default_all().len(), 31);
+
+        let mut collected: Hours = (&hours).into_iter().filter(|x| *x != H12).collect();
+        assert_eq!(collected, H9 | H14 | H15 | H16);
+        collected.extend([H12, H23]);
+        assert_eq!(collected, H9 | H12 | H23 | H14 | H15 | H16);
+        assert_eq!(collected.clone() - H23, hours);
+
+        let set: HashSet<WeekDays> = [W1 | W2, W2 | W1, Default::default()].into_iter().collect();
+        assert_eq!(set.len(), 2);
+    }
+}
@@ -0,0 +1,234 @@
+use crate::combinator::{Except, Intersect, Union};
+use crate::error::{Error, Result};
+use crate::exclusion::{Excluded, Exclusion};
+use crate::jitter::{Jitter, Jittered};
+use crate::limit::Limited;
+use crate::window::Window;
+use chrono::{Duration, Local, NaiveDateTime};
+use std::ops::{Bound, RangeBounds, Sub};
+
+/// 组合计划的最大查找范围（年）。公历400年一个循环，超过该范围仍找不到则认为不存在
+pub(crate) const SEARCH_YEARS: i64 = 400;
+
+/// 从now开始往后（forward）或往前的最远查找时间点
+pub(crate) fn horizon(now: NaiveDateTime, forward: bool) -> NaiveDateTime {
+    let range = Duration::days(SEARCH_YEARS * 366);
+    if forward {
+        now.checked_add_signed(range).unwrap_or(NaiveDateTime::MAX)
+    } else {
+        now.checked_sub_signed(range).unwrap_or(NaiveDateTime::MIN)
+    }
+}
+
+/// 把日期时间范围转成(start, end]：start不包含、end包含。范围不限制的一端按有效期截断
+pub(crate) fn span(
+    range: &impl RangeBounds<NaiveDateTime>,
+    (not_before, not_after): (Option<NaiveDateTime>, Option<NaiveDateTime>),
+) -> Result<(NaiveDateTime, NaiveDateTime)> {
+    let start = match range.start_bound() {
+        Bound::Unbounded => match not_before {
+            Some(first) => first.sub(Duration::seconds(1)),
+            None => {
+                return Err(Error::InvalidBound {
+                    field: "datetime",
+                    reason: "unbounded start without a valid range",
+                })
+            }
+        },
+        Bound::Included(first) => first.sub(Duration::seconds(1)),
+        Bound::Excluded(first) => *first,
+    };
+    let end = match range.end_bound() {
+        Bound::Unbounded => match not_after {
+            Some(end) => end,
+            None => {
+                return Err(Error::InvalidBound {
+                    field: "datetime",
+                    reason: "unbounded end without a valid range",
+                })
+            }
+        },
+        Bound::Included(end) => *end,
+        Bound::Excluded(end) => end.sub(Duration::seconds(1)),
+    };
+    if start >= end {
+        return Err(Error::empty_range("datetime", start, end));
+    }
+    Ok((start, end))
+}
+
+/// 定时计划：可计算上个/下个符合的时间点。
+///
+/// [`TimerConf`](crate::TimerConf)、[`FixedTimes`](crate::FixedTimes)等均实现了该trait，
+/// 因此可以用同一套调度逻辑驱动
+pub trait Schedule {
+    /// 以给定的时间点为起点(不包含该时点)，返回下个符合的时间点。没有则返回None
+    fn next_with_time(&self, now: NaiveDateTime) -> Option<NaiveDateTime>;
+    /// 以给定的时间点为起点(不包含该时点)，返回上个符合的时间点。没有则返回None
+    fn prev_with_time(&self, now: NaiveDateTime) -> Option<NaiveDateTime>;
+
+    /// 给定的时间点是否符合
+    fn matches(&self, datetime: NaiveDateTime) -> bool {
+        self.next_with_time(datetime.sub(Duration::seconds(1))) == Some(datetime)
+    }
+
+    /// 以给定的时间点为起点(不包含该时点)，返回下个不符合的时间点，用于跳过连续符合的时段。
+    /// 查找范围内一直符合则返回None。默认逐秒判断，连续符合的时段较长时应重写
+    fn next_unmatched(&self, now: NaiveDateTime) -> Option<NaiveDateTime> {
+        let limit = horizon(now, true);
+        let mut current = now.checked_add_signed(Duration::seconds(1))?;
+        while self.matches(current) {
+            if current > limit {
+                return None;
+            }
+            current = current.checked_add_signed(Duration::seconds(1))?;
+        }
+        Some(current)
+    }
+    /// 以给定的时间点为起点(不包含该时点)，返回上个不符合的时间点，见[`Schedule::next_unmatched`]
+    fn prev_unmatched(&self, now: NaiveDateTime) -> Option<NaiveDateTime> {
+        let limit = horizon(now, false);
+        let mut current = now.checked_sub_signed(Duration::seconds(1))?;
+        while self.matches(current) {
+            if current < limit {
+                return None;
+            }
+            current = current.checked_sub_signed(Duration::seconds(1))?;
+        }
+        Some(current)
+    }
+
+    /// 有效期：(最早, 最晚)的触发时间点，None表示该端不限制
+    fn valid_range(&self) -> (Option<NaiveDateTime>, Option<NaiveDateTime>) {
+        (None, None)
+    }
+
+    /// 以当前时间点为起点，返回距离下个符合时间点的时间间隔（s）。没有则返回None
+    fn next(&self) -> Option<u64> {
+        let now_local = Local::now().naive_local();
+        let next_local = self.next_with_time(now_local)?;
+        Some((next_local.timestamp() - now_local.timestamp()) as u64)
+    }
+
+    /// 在给定的日期时间范围内，返回符合的所有时间点。范围不限制的一端按有效期截断
+    fn datetimes(&self, range: impl RangeBounds<NaiveDateTime>) -> Result<Vec<NaiveDateTime>>
+    where
+        Self: Sized,
+    {
+        let (mut start, end) = span(&range, self.valid_range())?;
+        let mut date_times = Vec::new();
+        while let Some(next) = self.next_with_time(start) {
+            if next > end {
+                break;
+            }
+            date_times.push(next);
+            start = next;
+        }
+        Ok(date_times)
+    }
+
+    /// 以给定的时间点为起点(不包含该时点)，依次返回之后符合的时间点
+    fn iter_from(&self, start: NaiveDateTime) -> ScheduleIter<'_, Self>
+    where
+        Self: Sized,
+    {
+        ScheduleIter {
+            schedule: self,
+            current: Some(start),
+        }
+    }
+
+    /// 并集：符合任一计划即触发
+    fn union<O: Schedule>(self, other: O) -> Union<Self, O>
+    where
+        Self: Sized,
+    {
+        Union::new(self, other)
+    }
+    /// 交集：同时符合两个计划才触发
+    fn intersect<O: Schedule>(self, other: O) -> Intersect<Self, O>
+    where
+        Self: Sized,
+    {
+        Intersect::new(self, other)
+    }
+    /// 差集：符合本计划、且不符合other时才触发
+    fn except<O: Schedule>(self, other: O) -> Except<Self, O>
+    where
+        Self: Sized,
+    {
+        Except::new(self, other)
+    }
+    /// 有效期：最早的触发时间点（包含）
+    fn not_before(self, not_before: NaiveDateTime) -> Window<Self>
+    where
+        Self: Sized,
+    {
+        Window::new(self).not_before(not_before)
+    }
+    /// 有效期：最晚的触发时间点（包含）
+    fn not_after(self, not_after: NaiveDateTime) -> Window<Self>
+    where
+        Self: Sized,
+    {
+        Window::new(self).not_after(not_after)
+    }
+    /// 次数限制：以start为起点（包含），只触发前count次
+    fn limit(self, start: NaiveDateTime, count: usize) -> Limited<Self>
+    where
+        Self: Sized,
+    {
+        Limited::new(self, start, count)
+    }
+    /// 屏蔽规则：跳过屏蔽时段内的时间点
+    fn exclude(self, exclusion: Exclusion) -> Excluded<Self>
+    where
+        Self: Sized,
+    {
+        Excluded::new(self).exclude(exclusion)
+    }
+    /// 抖动：触发时间点按jitter往后推迟，错开同一时刻的大量任务
+    fn jitter(self, jitter: Jitter) -> Jittered<Self>
+    where
+        Self: Sized,
+    {
+        Jittered::new(self, jitter)
+    }
+}
+
+impl<S: Schedule + ?Sized> Schedule for Box<S> {
+    fn next_with_time(&self, now: NaiveDateTime) -> Option<NaiveDateTime> {
+        (**self).next_with_time(now)
+    }
+    fn prev_with_time(&self, now: NaiveDateTime) -> Option<NaiveDateTime> {
+        (**self).prev_with_time(now)
+    }
+    fn matches(&self, datetime: NaiveDateTime) -> bool {
+        (**self).matches(datetime)
+    }
+    fn next_unmatched(&self, now: NaiveDateTime) -> Option<NaiveDateTime> {
+        (**self).next_unmatched(now)
+    }
+    fn prev_unmatched(&self, now: NaiveDateTime) -> Option<NaiveDateTime> {
+        (**self).prev_unmatched(now)
+    }
+    fn valid_range(&self) -> (Option<NaiveDateTime>, Option<NaiveDateTime>) {
+        (**self).valid_range()
+    }
+}
+
+/// 定时计划的时间点迭代器，见[`Schedule::iter_from`]
+pub struct ScheduleIter<'a, S: Schedule> {
+    schedule: &'a S,
+    current: Option<NaiveDateTime>,
+}
+
+impl<'a, S: Schedule> Iterator for ScheduleIter<'a, S> {
+    type Item = NaiveDateTime;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next = self.schedule.next_with_time(self.current?);
+        self.current = next;
+        next
+    }
+}
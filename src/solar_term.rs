@@ -0,0 +1,361 @@
+use crate::conf::{MonthDays, SolarTerms};
+use crate::data::{MonthDay, SolarTerm};
+use crate::traits::{AsBizData, ConfigOperator, FromData};
+use chrono::{Datelike, NaiveDate};
+
+/// 内置节气表的年份范围
+const FIRST_YEAR: i32 = 1900;
+const LAST_YEAR: i32 = 2100;
+
+/// 各节气最早可能的日子，按小寒到冬至的顺序
+const TERM_BASE: [u32; 24] = [
+    4, 19, 3, 18, 4, 19, 4, 19, 4, 20, 4, 20, 6, 22, 6, 22, 6, 22, 7, 22, 6, 21, 6, 21,
+];
+
+/// 1900-2100年的节气日期（北京时间），每年一项：第2k、2k+1位为第k+1个节气相对[`TERM_BASE`]的天数。
+/// 按Reingold & Dershowitz《Calendrical Calculations》的太阳视黄经算法，求出黄经每隔15°的时刻后
+/// 换算为北京时间取日期，算法及校验见本文件的测试`test_table`
+#[rustfmt::skip]
+const TERM_INFO: [u64; 201] = [
+    // 1900
+    0x5aa665a65a56, 0x6aaaa6aa9a5a, 0xaaaaaabaaa6a, 0xaaabbabbafaa, 0x5aa665a65aab,
+    // 1905
+    0x6aaaa6aa9a5a, 0xaaaaaaaaaa6a, 0xaaabbabbafaa, 0x5aa665a65aab, 0x6aaaa6aa9a5a,
+    // 1910
+    0xaaaaaaaaaa6a, 0xaaabbabbafaa, 0x56a665a65aab, 0x6aa6a6aa9a56, 0xaaaaaaaa9a5a,
+    // 1915
+    0xaaabaabaaeaa, 0x569665a65aaa, 0x6aa6a6a69a56, 0x6aaaaaaa9a5a, 0xaaabaabaaeaa,
+    // 1920
+    0x569665a65aaa, 0x5aa6a6a65a56, 0x6aaaaaaa9a5a, 0xaaabaabaaa6a, 0x569665a65aaa,
+    // 1925
+    0x5aa6a6a65a56, 0x6aaaa6aa9a5a, 0xaaabaabaaa6a, 0x555665a65aaa, 0x5aa665a65a56,
+    // 1930
+    0x6aaaa6aa9a5a, 0xaaaaaabaaa6a, 0x555665665aaa, 0x5aa665a65a56, 0x6aaaa6aa9a5a,
+    // 1935
+    0xaaaaaaaaaa6a, 0x555665665aaa, 0x5aa665a65a56, 0x6aaaa6aa9a5a, 0xaaaaaaaaaa6a,
+    // 1940
+    0x555665665aaa, 0x5aa665a65a56, 0x6aaaa6aa9a5a, 0xaaaaaaaaaa6a, 0x555665655aaa,
+    // 1945
+    0x569665a65a56, 0x6aa6a6aa9a56, 0xaaaaaaaa9a5a, 0x5556556559aa, 0x569665a65a55,
+    // 1950
+    0x6aa6a6a65a56, 0x6aaaaaaa9a5a, 0x5556556559aa, 0x569665a65a55, 0x5aa6a6a65a56,
+    // 1955
+    0x6aaaa6aa9a5a, 0x5556556555aa, 0x569665a65a55, 0x5aa665a65a56, 0x6aaaa6aa9a5a,
+    // 1960
+    0x55555565556a, 0x555665665a55, 0x5aa665a65a56, 0x6aaaa6aa9a5a, 0x55555565556a,
+    // 1965
+    0x555665665a55, 0x5aa665a65a56, 0x6aaaa6aa9a5a, 0x55555555556a, 0x555665665a55,
+    // 1970
+    0x5aa665a65a56, 0x6aaaa6aa9a5a, 0x55555555556a, 0x555665655a55, 0x5aa665a65a56,
+    // 1975
+    0x6aa6a6aa9a5a, 0x55555555456a, 0x555655655a55, 0x5a9665a65a56, 0x6aa6a6a69a56,
+    // 1980
+    0x55555555456a, 0x555655655a55, 0x569665a65a56, 0x6aa6a6a65a56, 0x55555155455a,
+    // 1985
+    0x555655655955, 0x569665a65a55, 0x5aa6a5a65a56, 0x15555155455a, 0x555555655555,
+    // 1990
+    0x569665665a55, 0x5aa665a65a56, 0x15555155455a, 0x555555655515, 0x555665665a55,
+    // 1995
+    0x5aa665a65a56, 0x15555155455a, 0x555555555515, 0x555665665a55, 0x5aa665a65a56,
+    // 2000
+    0x15555155455a, 0x555555555515, 0x555665665a55, 0x5aa665a65a56, 0x15555155455a,
+    // 2005
+    0x555555555515, 0x555655655a55, 0x5aa665a65a56, 0x15515155455a, 0x555555554515,
+    // 2010
+    0x555655655a55, 0x5a9665a65a56, 0x15515151455a, 0x555551554515, 0x555655655a55,
+    // 2015
+    0x569665a65a56, 0x155151510556, 0x555551554505, 0x555655655955, 0x569665665a55,
+    // 2020
+    0x155110510556, 0x155551554505, 0x555555655555, 0x569665665a55, 0x055110510556,
+    // 2025
+    0x155551554505, 0x555555555515, 0x555665665a55, 0x055110510556, 0x155551554505,
+    // 2030
+    0x555555555515, 0x555665665a55, 0x055110510556, 0x155551554505, 0x555555555515,
+    // 2035
+    0x555655655a55, 0x055110510556, 0x155551554505, 0x555555555515, 0x555655655a55,
+    // 2040
+    0x055110510556, 0x155151514505, 0x555555554515, 0x555655655a55, 0x054110510556,
+    // 2045
+    0x155151510505, 0x555551554515, 0x555655655a55, 0x014110110556, 0x155110510501,
+    // 2050
+    0x555551554505, 0x555555655555, 0x014110110555, 0x155110510501, 0x555551554505,
+    // 2055
+    0x555555555555, 0x014110110555, 0x055110510501, 0x155551554505, 0x555555555555,
+    // 2060
+    0x000110110555, 0x055110510501, 0x155551554505, 0x555555555515, 0x000110110555,
+    // 2065
+    0x055110510501, 0x155551554505, 0x555555555515, 0x000100100555, 0x055110510501,
+    // 2070
+    0x155151514505, 0x555555555515, 0x000100100555, 0x054110510501, 0x155151514505,
+    // 2075
+    0x555551554515, 0x000100100555, 0x054110510501, 0x155150510505, 0x555551554515,
+    // 2080
+    0x000100100555, 0x014110110501, 0x155110510505, 0x555551554505, 0x000000100055,
+    // 2085
+    0x014110110500, 0x155110510501, 0x555551554505, 0x000000000055, 0x014110110500,
+    // 2090
+    0x055110510501, 0x155551554505, 0x000000000055, 0x000110110500, 0x055110510501,
+    // 2095
+    0x155551554505, 0x000000000015, 0x000100110500, 0x055110510501, 0x155551554505,
+    // 2100
+    0x555555555515,
+];
+
+/// 内置节气表的年份范围（两端都包含）
+pub(crate) fn years() -> (i32, i32) {
+    (FIRST_YEAR, LAST_YEAR)
+}
+
+impl SolarTerm {
+    /// 该节气在公历year年的日期（北京时间）；超出1900-2100年时返回None
+    pub fn date(self, year: i32) -> Option<NaiveDate> {
+        if !(FIRST_YEAR..=LAST_YEAR).contains(&year) {
+            return None;
+        }
+        let index = self.as_data() - 1;
+        let offset = (TERM_INFO[(year - FIRST_YEAR) as usize] >> (index * 2)) & 0b11;
+        // 每月两个节气：小寒、大寒在1月，立春、雨水在2月……
+        Some(NaiveDate::from_ymd(
+            year,
+            (index / 2 + 1) as u32,
+            TERM_BASE[index as usize] + offset as u32,
+        ))
+    }
+    /// 给定日期对应的节气，不是节气当天时返回None
+    pub fn from_date(date: NaiveDate) -> Option<Self> {
+        let index = date.month() as u64 * 2;
+        [index - 1, index]
+            .into_iter()
+            .map(Self::from_data)
+            .find(|term| term.date(date.year()) == Some(date))
+    }
+}
+
+impl SolarTerms {
+    /// 给定月份里被选中的节气日期
+    pub(crate) fn month_days(&self, year: i32, month: u32) -> MonthDays {
+        let index = month as u64 * 2;
+        [index - 1, index]
+            .into_iter()
+            .map(SolarTerm::from_data)
+            .filter(|term| self.contain(*term))
+            .filter_map(|term| term.date(year))
+            .fold(MonthDays::_default(), |days, date| {
+                days.add(MonthDay::from_data(date.day() as u64))
+            })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::conf::test::datetime;
+    use crate::*;
+    use chrono::{Datelike, NaiveDate};
+
+    /// 太阳视黄经的周期项：(振幅, 初相, 频率)，见Reingold & Dershowitz《Calendrical Calculations》
+    /// 第4版的solar-longitude（源自Bretagnon & Simon 1986）
+    #[rustfmt::skip]
+    const LONGITUDE_TERMS: [(f64, f64, f64); 49] = [
+        (403406.0, 270.54861, 0.9287892), (195207.0, 340.19128, 35999.1376958),
+        (119433.0, 63.91854, 35999.4089666), (112392.0, 331.26220, 35998.7287385),
+        (3891.0, 317.843, 71998.20261), (2819.0, 86.631, 71998.4403),
+        (1721.0, 240.052, 36000.35726), (660.0, 310.26, 71997.4812), (350.0, 247.23, 32964.4678),
+        (334.0, 260.87, -19.4410), (314.0, 297.82, 445267.1117), (268.0, 343.14, 45036.8840),
+        (242.0, 166.79, 3.1008), (234.0, 81.53, 22518.4434), (158.0, 3.50, -19.9739),
+        (132.0, 132.75, 65928.9345), (129.0, 182.95, 9038.0293), (114.0, 162.03, 3034.7684),
+        (99.0, 29.8, 33718.148), (93.0, 266.4, 3034.448), (86.0, 249.2, -2280.773),
+        (78.0, 157.6, 29929.992), (72.0, 257.8, 31556.493), (68.0, 185.1, 149.588),
+        (64.0, 69.9, 9037.750), (46.0, 8.0, 107997.405), (38.0, 197.1, -4444.176),
+        (37.0, 250.4, 151.771), (32.0, 65.3, 67555.316), (29.0, 162.7, 31556.080),
+        (28.0, 341.5, -4561.540), (27.0, 291.6, 107996.706), (27.0, 98.5, 1221.655),
+        (25.0, 146.7, 62894.167), (24.0, 110.0, 31437.369), (21.0, 5.2, 14578.298),
+        (21.0, 342.6, -31931.757), (20.0, 230.9, 34777.243), (18.0, 256.1, 1221.999),
+        (17.0, 45.3, 62894.511), (14.0, 242.9, -4442.039), (13.0, 115.2, 107997.909),
+        (13.0, 151.8, 119.066), (13.0, 285.3, 16859.071), (12.0, 53.3, -4.578),
+        (10.0, 126.6, 26895.292), (10.0, 205.7, -39.127), (10.0, 85.9, 12297.536),
+        (10.0, 146.1, 90073.778),
+    ];
+
+    /// 力学时与世界时之差（日）各分段的多项式系数，只保留1800-2150年
+    const DELTA_T_2006: [f64; 3] = [62.92, 0.32217, 0.005589];
+    #[rustfmt::skip]
+    const DELTA_T_1987: [f64; 6] = [
+        63.86, 0.3345, -0.060374, 0.0017275, 0.000651814, 0.00002373599,
+    ];
+    #[rustfmt::skip]
+    const DELTA_T_1900: [f64; 8] = [
+        -0.00002, 0.000297, 0.025184, -0.181133, 0.553040, -0.861938, 0.677066, -0.212591,
+    ];
+    #[rustfmt::skip]
+    const DELTA_T_1800: [f64; 11] = [
+        -0.000009, 0.003844, 0.083563, 0.865736, 4.867575, 15.845535, 31.332267, 38.291999,
+        28.316289, 11.636204, 2.043794,
+    ];
+
+    fn poly(x: f64, coefficients: &[f64]) -> f64 {
+        coefficients.iter().rev().fold(0.0, |sum, a| sum * x + a)
+    }
+
+    /// 力学时与世界时之差（日）
+    fn ephemeris_correction(moment: f64) -> f64 {
+        let year = (moment / 365.2425 + 1.0) as i32;
+        let mid_year = NaiveDate::from_ymd(year, 7, 1).num_days_from_ce() as f64;
+        let c = (mid_year - 693596.0) / 36525.0;
+        let y2000 = (year - 2000) as f64;
+        match year {
+            2051..=2150 => {
+                let y1820 = (year - 1820) as f64 / 100.0;
+                (-20.0 + 32.0 * y1820 * y1820 + 0.5628 * (2150 - year) as f64) / 86400.0
+            }
+            2006..=2050 => poly(y2000, &DELTA_T_2006) / 86400.0,
+            1987..=2005 => poly(y2000, &DELTA_T_1987) / 86400.0,
+            1900..=1986 => poly(c, &DELTA_T_1900),
+            _ => poly(c, &DELTA_T_1800),
+        }
+    }
+
+    /// moment（从公元1年1月1日0时起算的日数，世界时）的太阳视黄经（度）
+    fn solar_longitude(moment: f64) -> f64 {
+        let c = (moment + ephemeris_correction(moment) - 730120.5) / 36525.0;
+        let lambda: f64 = LONGITUDE_TERMS
+            .iter()
+            .map(|(x, y, z)| x * (y + z * c).to_radians().sin())
+            .sum();
+        let lambda = lambda * 0.000005729577951308232 + 282.7771834 + 36000.76953744 * c;
+        let aberration = 0.0000974 * (177.63 + 35999.01848 * c).to_radians().cos() - 0.005575;
+        let a = 124.90 - 1934.134 * c + 0.002063 * c * c;
+        let b = 201.11 + 72001.5377 * c + 0.00057 * c * c;
+        let nutation = -0.004778 * a.to_radians().sin() - 0.0003667 * b.to_radians().sin();
+        (lambda + aberration + nutation).rem_euclid(360.0)
+    }
+
+    /// 第index个节气（0为小寒，黄经285°）在公历year年的日期（北京时间）
+    fn term_date(year: i32, index: usize) -> NaiveDate {
+        let angle = (285.0 + 15.0 * index as f64) % 360.0;
+        // 从该节气的大致日期前后12天内二分查找
+        let base = NaiveDate::from_ymd(year, 1, 1).num_days_from_ce() as f64
+            + 15.2184 * index as f64
+            + 5.0;
+        let delta = |moment: f64| (solar_longitude(moment) - angle + 540.0) % 360.0 - 180.0;
+        let (mut low, mut high) = (base - 12.0, base + 12.0);
+        for _ in 0..60 {
+            let middle = (low + high) / 2.0;
+            if delta(middle) < 0.0 {
+                low = middle;
+            } else {
+                high = middle;
+            }
+        }
+        NaiveDate::from_num_days_from_ce((low + 8.0 / 24.0).floor() as i32)
+    }
+
+    #[test]
+    fn test_table() {
+        // 内置的节气表与天文计算的结果一致
+        for year in 1900..=2100 {
+            for index in 0..24 {
+                assert_eq!(
+                    SolarTerm::from_data(index as u64 + 1).date(year),
+                    Some(term_date(year, index)),
+                    "{} {}",
+                    year,
+                    index
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_solar_term() -> anyhow::Result<()> {
+        // 2025年各节气的日子，每月两个
+        let days = [
+            5, 20, 3, 18, 5, 20, 4, 20, 5, 21, 5, 21, 7, 22, 7, 23, 7, 23, 8, 23, 7, 22, 7, 21,
+        ];
+        for (index, day) in days.into_iter().enumerate() {
+            assert_eq!(
+                SolarTerm::from_data(index as u64 + 1).date(2025),
+                Some(NaiveDate::from_ymd(2025, index as u32 / 2 + 1, day))
+            );
+        }
+        // 2021年冬至在23:59，仍为21日
+        assert_eq!(
+            SolarTerm::DongZhi.date(2021),
+            Some(NaiveDate::from_ymd(2021, 12, 21))
+        );
+        assert_eq!(
+            SolarTerm::from_date(NaiveDate::from_ymd(2024, 4, 4)),
+            Some(SolarTerm::QingMing)
+        );
+        assert_eq!(SolarTerm::from_date(NaiveDate::from_ymd(2024, 4, 5)), None);
+        assert_eq!(SolarTerm::QingMing.date(2101), None);
+
+        // 清明、冬至的8点灌溉
+        let conf = configure_solar_terms(SolarTerms::default_array(&[
+            SolarTerm::QingMing,
+            SolarTerm::DongZhi,
+        ]))
+        .build_with_hours(Hours::default_value(H8))
+        .build_with_minuter(Minuters::default_value(M0))
+        .build_with_second(Seconds::default_value(S0));
+        assert_eq!(
+            conf.datetimes(datetime(2023, 6, 1, 0, 0, 0)..datetime(2025, 6, 1, 0, 0, 0))?,
+            vec![
+                datetime(2023, 12, 22, 8, 0, 0),
+                datetime(2024, 4, 4, 8, 0, 0),
+                datetime(2024, 12, 21, 8, 0, 0),
+                datetime(2025, 4, 4, 8, 0, 0),
+            ]
+        );
+        assert_eq!(
+            conf.prev_with_time(datetime(2024, 4, 4, 8, 0, 0)),
+            datetime(2023, 12, 22, 8, 0, 0)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_table_bounds() -> anyhow::Result<()> {
+        // 表的两端
+        assert_eq!(
+            SolarTerm::XiaoHan.date(1900),
+            Some(NaiveDate::from_ymd(1900, 1, 6))
+        );
+        assert_eq!(
+            SolarTerm::DongZhi.date(2100),
+            Some(NaiveDate::from_ymd(2100, 12, 22))
+        );
+        assert_eq!(SolarTerm::DongZhi.date(1899), None);
+        assert_eq!(SolarTerm::XiaoHan.date(2101), None);
+        assert_eq!(SolarTerm::from_date(NaiveDate::from_ymd(2101, 1, 5)), None);
+
+        // 超出表的范围时不再触发，也不会panic
+        let conf = configure_solar_terms(SolarTerms::default_array(&[
+            SolarTerm::XiaoHan,
+            SolarTerm::DongZhi,
+        ]))
+        .build_with_hours(Hours::default_value(H0))
+        .build_with_minuter(Minuters::default_value(M0))
+        .build_with_second(Seconds::default_value(S0));
+        assert_eq!(
+            Schedule::next_with_time(&conf, datetime(2100, 12, 22, 0, 0, 0)),
+            None
+        );
+        assert_eq!(
+            Schedule::next_with_time(&conf, datetime(2101, 3, 1, 0, 0, 0)),
+            None
+        );
+        assert_eq!(
+            Schedule::prev_with_time(&conf, datetime(1900, 1, 6, 0, 0, 0)),
+            None
+        );
+        assert_eq!(
+            Schedule::prev_with_time(&conf, datetime(1900, 3, 1, 0, 0, 0)),
+            Some(datetime(1900, 1, 6, 0, 0, 0))
+        );
+        assert_eq!(
+            Schedule::next_with_time(&conf, datetime(1800, 1, 1, 0, 0, 0)),
+            Some(datetime(1900, 1, 6, 0, 0, 0))
+        );
+        Ok(())
+    }
+}
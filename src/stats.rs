@@ -0,0 +1,381 @@
+use crate::compute::MonthMasks;
+use crate::conf::TimerConf;
+use crate::data::{Hour, Minuter, Second};
+use crate::error::Result;
+use crate::schedule::span;
+use crate::traits::{bits_below, bits_from, bits_to, lowest, AsBizData, ConfigOperator, FromData};
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+use std::ops::{Add, RangeBounds};
+
+const DAY_SECONDS: i64 = 24 * 3600;
+
+impl TimerConf {
+    /// 给定的日期时间范围内触发的次数。按选中的天数乘以每天的触发次数计算，不逐个列出时间点
+    pub fn count_between(&self, range: impl RangeBounds<NaiveDateTime>) -> Result<u64> {
+        // (start, end] 转成 [first, end]
+        let (start, end) = span(&range, (None, None))?;
+        let first = start.add(Duration::seconds(1));
+        let (first_date, end_date) = (first.date(), end.date());
+        if first_date == end_date {
+            return Ok(match self.contains_date(first_date) {
+                true => self.times_until(end.time()) - self.times_before(first.time()),
+                false => 0,
+            });
+        }
+        let mut count = self.count_days(first_date.succ(), end_date.pred()) * self.times_per_day();
+        if self.contains_date(first_date) {
+            count += self.times_per_day() - self.times_before(first.time());
+        }
+        if self.contains_date(end_date) {
+            count += self.times_until(end.time());
+        }
+        Ok(count)
+    }
+    /// (最小, 最大)的触发间隔，触发不到2次时为None。统计范围见[`TimerConf::average_interval`]。
+    /// 要同时用到两者时用这个，只统计一遍
+    pub fn intervals(&self) -> Option<(Duration, Duration)> {
+        self.interval_seconds()
+            .map(|(min, max)| (Duration::seconds(min), Duration::seconds(max)))
+    }
+    /// 最小的触发间隔，触发不到2次时为None。统计范围见[`TimerConf::average_interval`]
+    pub fn min_interval(&self) -> Option<Duration> {
+        self.intervals().map(|(min, _)| min)
+    }
+    /// 最大的触发间隔，触发不到2次时为None。统计范围见[`TimerConf::average_interval`]
+    pub fn max_interval(&self) -> Option<Duration> {
+        self.intervals().map(|(_, max)| max)
+    }
+    /// 平均的触发间隔，即统计范围的总时长除以触发次数，不触发时为None。
+    ///
+    /// 日期按公历400年循环时，统计2000-2399年。日期依赖数据表时只统计有数据的年份，
+    /// 以免把数据表之外的结果（如节假日日历没有安排的年份按周一到周五算）当成规律：
+    /// 节假日按日历的[`HolidayCalendar::covered_years`](crate::HolidayCalendar::covered_years)，
+    /// 农历、节气按内置的表，都不限时统计2000-2099年
+    pub fn average_interval(&self) -> Option<Duration> {
+        let (first, last) = self.stat_years();
+        let start = NaiveDate::from_ymd(first, 1, 1);
+        let end = NaiveDate::from_ymd(last + 1, 1, 1);
+        let count = self.count_days(start, end.pred()) * self.times_per_day();
+        match count {
+            0 => None,
+            _ => Some(Duration::seconds(
+                (end - start).num_seconds() / count as i64,
+            )),
+        }
+    }
+
+    /// 每天触发的次数
+    fn times_per_day(&self) -> u64 {
+        (self.hours.len() * self.minuters.len() * self.seconds.len()) as u64
+    }
+    /// 一天内不晚于time的触发次数
+    fn times_until(&self, time: NaiveTime) -> u64 {
+        let (hour, minuter, second) = (time.hour(), time.minute(), time.second());
+        let (hours, minuters, seconds) =
+            (self.hours._val(), self.minuters._val(), self.seconds._val());
+        let per_minuter = self.seconds.len() as u64;
+        let per_hour = self.minuters.len() as u64 * per_minuter;
+        let mut count = (hours & bits_below(hour)).count_ones() as u64 * per_hour;
+        if hours & (1 << hour) > 0 {
+            count += (minuters & bits_below(minuter)).count_ones() as u64 * per_minuter;
+            if minuters & (1 << minuter) > 0 {
+                count += (seconds & bits_to(second)).count_ones() as u64;
+            }
+        }
+        count
+    }
+    /// 一天内早于time的触发次数
+    fn times_before(&self, time: NaiveTime) -> u64 {
+        let hit = self.hours.contain(Hour::from_data(time.hour() as u64))
+            && self
+                .minuters
+                .contain(Minuter::from_data(time.minute() as u64))
+            && self
+                .seconds
+                .contain(Second::from_data(time.second() as u64));
+        self.times_until(time) - hit as u64
+    }
+    /// first..=end内选中的天数
+    fn count_days(&self, first: NaiveDate, end: NaiveDate) -> u64 {
+        let mut count = 0;
+        self.for_each_month(first, end, |_, _, mask| {
+            count += mask.count_ones() as u64;
+        });
+        count
+    }
+    /// 按顺序遍历first..=end内选中的日期
+    fn for_each_day(&self, first: NaiveDate, end: NaiveDate, mut f: impl FnMut(NaiveDate)) {
+        self.for_each_month(first, end, |year, month, mut mask| {
+            while let Some(day) = lowest(mask) {
+                mask &= mask - 1;
+                f(NaiveDate::from_ymd(year, month, day as u32));
+            }
+        });
+    }
+    /// 按顺序遍历first..=end内的月份及其中选中日期的掩码，每种月份形状只算一次
+    fn for_each_month(&self, first: NaiveDate, end: NaiveDate, mut f: impl FnMut(i32, u32, u64)) {
+        let mut masks = MonthMasks::new(self.days.clone());
+        let (mut year, mut month) = (first.year(), first.month());
+        while (year, month) <= (end.year(), end.month()) {
+            let mut mask = masks.mask_mut(year, month);
+            if (year, month) == (first.year(), first.month()) {
+                mask &= bits_from(first.day());
+            }
+            if (year, month) == (end.year(), end.month()) {
+                mask &= bits_to(end.day());
+            }
+            f(year, month, mask);
+            if month == 12 {
+                year += 1;
+                month = 1;
+            } else {
+                month += 1;
+            }
+        }
+    }
+    /// 统计的年份范围（两端都包含），见[`TimerConf::average_interval`]
+    fn stat_years(&self) -> (i32, i32) {
+        if self.days.is_periodic() {
+            return (2000, 2399);
+        }
+        let (first, last) = self.days.calendar_years().unwrap_or((2000, 2099));
+        let (table_first, table_last) = self.days.years();
+        (
+            first.max(table_first.unwrap_or(first)),
+            last.min(table_last.unwrap_or(last)),
+        )
+    }
+    /// (最小, 最大)的触发间隔（秒）
+    fn interval_seconds(&self) -> Option<(i64, i64)> {
+        let mut min: Option<i64> = None;
+        let mut max: Option<i64> = None;
+        let mut update = |gap: i64| {
+            min = Some(min.map_or(gap, |x| x.min(gap)));
+            max = Some(max.map_or(gap, |x| x.max(gap)));
+        };
+        // 一天内相邻时刻的间隔
+        let mut first_time = None;
+        let mut last_time = None;
+        for hour in self.hours.iter() {
+            for minuter in self.minuters.iter() {
+                for second in self.seconds.iter() {
+                    let time =
+                        (hour.as_data() * 3600 + minuter.as_data() * 60 + second.as_data()) as i64;
+                    if let Some(last) = last_time {
+                        update(time - last);
+                    }
+                    first_time.get_or_insert(time);
+                    last_time = Some(time);
+                }
+            }
+        }
+        let (first_time, last_time) = (first_time?, last_time?);
+        // 相邻选中日期的间隔：前一天的最后一个时刻到后一天的第一个时刻。
+        // 按公历循环时多统计28年，以覆盖跨循环的间隔
+        let (first, mut last) = self.stat_years();
+        if self.days.is_periodic() {
+            last += 28;
+        }
+        let (start, end) = (
+            NaiveDate::from_ymd(first, 1, 1),
+            NaiveDate::from_ymd(last, 12, 31),
+        );
+        let mut last_day: Option<NaiveDate> = None;
+        self.for_each_day(start, end, |day| {
+            if let Some(last) = last_day {
+                update((day - last).num_days() * DAY_SECONDS - last_time + first_time);
+            }
+            last_day = Some(day);
+        });
+        Some((min?, max?))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::conf::test::datetime;
+    use crate::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_count_between() -> anyhow::Result<()> {
+        let confs = [
+            configure_weekday(WeekDays::default_range(W1..=W5)?)
+                .build_with_hours(Hours::default_array(&[H9, H18]))
+                .build_with_minuter(Minuters::every(20)?)
+                .build_with_second(Seconds::default_array(&[S0, S30])),
+            configure_monthday(MonthDays::default_value(D31))
+                .build_with_hours(Hours::default_all())
+                .build_with_minuter(Minuters::default_value(M59))
+                .build_with_second(Seconds::default_value(S59)),
+            configure_workday(ChinaCalendar::bundled())
+                .build_with_hours(Hours::default_value(H0))
+                .build_with_minuter(Minuters::default_value(M0))
+                .build_with_second(Seconds::default_value(S0)),
+        ];
+        let ranges = [
+            (
+                datetime(2024, 10, 1, 0, 0, 0),
+                datetime(2024, 11, 1, 0, 0, 0),
+            ),
+            (
+                datetime(2024, 10, 31, 9, 20, 0),
+                datetime(2024, 10, 31, 18, 20, 30),
+            ),
+            (
+                datetime(2024, 10, 31, 9, 20, 1),
+                datetime(2024, 10, 31, 9, 20, 29),
+            ),
+            (
+                datetime(2023, 12, 31, 23, 59, 59),
+                datetime(2025, 3, 3, 0, 0, 0),
+            ),
+        ];
+        for conf in confs.iter() {
+            for (start, end) in ranges {
+                assert_eq!(
+                    conf.count_between(start..end)?,
+                    conf.datetimes(start..end)?.len() as u64,
+                    "{:?} {}..{}",
+                    conf,
+                    start,
+                    end
+                );
+                assert_eq!(
+                    conf.count_between(start..=end)?,
+                    conf.datetimes(start..=end)?.len() as u64
+                );
+            }
+        }
+        // 10月的工作日每天0点：国庆节后的工作日，及调休上班的10月12日
+        assert_eq!(
+            confs[2]
+                .count_between(datetime(2024, 10, 1, 0, 0, 0)..datetime(2024, 11, 1, 0, 0, 0))?,
+            19
+        );
+        assert!(confs[0]
+            .count_between(..datetime(2024, 10, 1, 0, 0, 0))
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_intervals() -> anyhow::Result<()> {
+        // 工作日9点、18点
+        let conf = configure_weekday(WeekDays::default_range(W1..=W5)?)
+            .build_with_hours(Hours::default_array(&[H9, H18]))
+            .build_with_minuter(Minuters::default_value(M0))
+            .build_with_second(Seconds::default_value(S0));
+        assert_eq!(conf.min_interval(), Some(Duration::hours(9)));
+        // 周五18点到周一9点
+        assert_eq!(conf.max_interval(), Some(Duration::hours(63)));
+        // 每周10次
+        assert_eq!(
+            conf.average_interval(),
+            Some(Duration::seconds(7 * 24 * 3600 / 10))
+        );
+
+        let conf = configure_monthday(MonthDays::default_all())
+            .build_with_hours(Hours::default_all())
+            .build_with_minuter(Minuters::every(15)?)
+            .build_with_second(Seconds::default_value(S0));
+        assert_eq!(conf.min_interval(), Some(Duration::minutes(15)));
+        assert_eq!(conf.max_interval(), Some(Duration::minutes(15)));
+        assert_eq!(conf.average_interval(), Some(Duration::minutes(15)));
+
+        let conf = configure_monthday(MonthDays::default_value(D31))
+            .build_with_hours(Hours::default_value(H0))
+            .build_with_minuter(Minuters::default_value(M0))
+            .build_with_second(Seconds::default_value(S0));
+        assert_eq!(conf.min_interval(), Some(Duration::days(31)));
+        assert_eq!(conf.max_interval(), Some(Duration::days(61)));
+
+        // 每年最后一天，只触发一次的一天内没有间隔
+        let conf = configure_yearday(YearDays::default_last())
+            .build_with_hours(Hours::default_value(H0))
+            .build_with_minuter(Minuters::default_value(M0))
+            .build_with_second(Seconds::default_value(S0));
+        assert_eq!(conf.min_interval(), Some(Duration::days(365)));
+        assert_eq!(conf.max_interval(), Some(Duration::days(366)));
+
+        let conf = configure_monthday(MonthDays::default_value(D1))
+            .build_with_hours(Hours::default())
+            .build_with_minuter(Minuters::default_value(M0))
+            .build_with_second(Seconds::default_value(S0));
+        assert_eq!(conf.min_interval(), None);
+        assert_eq!(conf.average_interval(), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_count_between_edge() -> anyhow::Result<()> {
+        let conf = configure_monthday(MonthDays::default_value(D29))
+            .build_with_hours(Hours::default_value(H12))
+            .build_with_minuter(Minuters::default_value(M0))
+            .build_with_second(Seconds::default_value(S0));
+        // 只包含一秒
+        let time = datetime(2024, 2, 29, 12, 0, 0);
+        assert_eq!(conf.count_between(time..=time)?, 1);
+        assert_eq!(
+            conf.count_between(time + Duration::seconds(1)..=time + Duration::seconds(1))?,
+            0
+        );
+        // 2100年2月没有29日
+        assert_eq!(
+            conf.count_between(datetime(2100, 1, 1, 0, 0, 0)..datetime(2101, 1, 1, 0, 0, 0))?,
+            11
+        );
+        // 不包含结束时点，起止相同时范围为空
+        assert!(conf.count_between(time..time).is_err());
+
+        let conf = configure_monthday(MonthDays::default_value(D1))
+            .build_with_hours(Hours::default())
+            .build_with_minuter(Minuters::default_value(M0))
+            .build_with_second(Seconds::default_value(S0));
+        assert_eq!(
+            conf.count_between(datetime(2024, 1, 1, 0, 0, 0)..datetime(2025, 1, 1, 0, 0, 0))?,
+            0
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_intervals_edge() -> anyhow::Result<()> {
+        // 只在闰年的最后一天触发：2096年之后的下一个闰年是2104年
+        let conf = configure_yearday(YearDays::default_value(366)?)
+            .build_with_hours(Hours::default_value(H0))
+            .build_with_minuter(Minuters::default_value(M0))
+            .build_with_second(Seconds::default_value(S0));
+        assert_eq!(conf.min_interval(), Some(Duration::days(1461)));
+        assert_eq!(conf.max_interval(), Some(Duration::days(2921)));
+        assert_eq!(
+            conf.intervals(),
+            Some((Duration::days(1461), Duration::days(2921)))
+        );
+        // 400年里有97个闰年
+        assert_eq!(
+            conf.average_interval(),
+            Some(Duration::seconds(146097 * 24 * 3600 / 97))
+        );
+
+        // 节假日日历只统计有安排的年份，与逐个列出的结果一致
+        let conf = configure_workday(ChinaCalendar::bundled())
+            .build_with_hours(Hours::default_value(H9))
+            .build_with_minuter(Minuters::default_value(M0))
+            .build_with_second(Seconds::default_value(S0));
+        let times = conf.datetimes(datetime(2023, 1, 1, 0, 0, 0)..datetime(2027, 1, 1, 0, 0, 0))?;
+        let gaps: Vec<Duration> = times.windows(2).map(|x| x[1] - x[0]).collect();
+        assert_eq!(
+            conf.intervals(),
+            Some((*gaps.iter().min().unwrap(), *gaps.iter().max().unwrap()))
+        );
+        assert_eq!(
+            conf.average_interval(),
+            Some(Duration::seconds(
+                (datetime(2027, 1, 1, 0, 0, 0) - datetime(2023, 1, 1, 0, 0, 0)).num_seconds()
+                    / times.len() as i64
+            ))
+        );
+        Ok(())
+    }
+}
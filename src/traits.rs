@@ -1,4 +1,6 @@
-use anyhow::{bail, Result};
+use crate::error::{Error, Result};
+use crate::hash::stable_hash;
+use std::marker::PhantomData;
 use std::ops::{Bound, RangeBounds};
 
 pub trait Computer {
@@ -23,8 +25,12 @@ pub trait ConfigOperator: Sized {
     const MAX: u64;
     /// 满值：即全选的值，比如星期7天全选，则为二进制1111 1110
     const DEFAULT_MAX: u64;
+    /// 配置项的名称，用于错误信息
+    const NAME: &'static str;
 
-    type DataTy: AsBizData<u64> + Copy + Clone;
+    /// 配置项的值。要求实现[`TryFromData`]及crate内部的FromData（相对0.3.6是不兼容的改动），
+    /// 因此ConfigOperator只能由本crate的配置类型实现
+    type DataTy: AsBizData<u64> + TryFromData<u64> + FromData<u64> + Copy + Clone;
 
     fn _default() -> Self;
     #[inline]
@@ -49,7 +55,7 @@ pub trait ConfigOperator: Sized {
         let mut val = ins._val();
         let mut index = Self::MIN;
         while index <= max.as_data() {
-            val |= 1 << index.clone();
+            val |= 1 << index;
             index += 1;
         }
         ins._val_mut(val);
@@ -67,12 +73,129 @@ pub trait ConfigOperator: Sized {
         self._val_mut(val);
         self
     }
+    /// 按整数值配置，如用户输入的[1, 15]。值超出范围时返回错误
+    fn default_data(vals: &[u64]) -> Result<Self> {
+        let ins = Self::_default();
+        ins.add_data(vals)
+    }
+    /// 按整数值添加，值超出范围时返回错误
+    fn add_data(self, vals: &[u64]) -> Result<Self> {
+        vals.iter().try_fold(self, |ins, val| {
+            Ok(ins.add(Self::DataTy::try_from_data(*val)?))
+        })
+    }
+    /// 按key的哈希值在区间内固定选中一个值，同一个key总是选中相同的值，如cron的H(0-29)
+    fn hashed(key: &str, range: impl RangeBounds<Self::DataTy>) -> Result<Self> {
+        let all = Self::default_range(range)?;
+        let index = (stable_hash(key) % all.len() as u64) as usize;
+        Ok(all
+            .iter()
+            .nth(index)
+            .map_or(Self::_default(), Self::default_value))
+    }
+    /// 在区间内每隔step选中一个值，起点按key的哈希值固定推迟step以内，如cron的H/15
+    fn hashed_step(key: &str, range: impl RangeBounds<Self::DataTy>, step: u64) -> Result<Self> {
+        if step == 0 {
+            return Err(Error::InvalidStep {
+                field: Self::NAME,
+                step,
+            });
+        }
+        let all = Self::default_range(range)?;
+        let offset = stable_hash(key) % step;
+        let mut ins = Self::_default();
+        let mut val = ins._val();
+        for (index, data) in all.iter().enumerate() {
+            if index as u64 % step == offset {
+                val |= 1 << data.as_data();
+            }
+        }
+        ins._val_mut(val);
+        Ok(ins)
+    }
+    /// 解析cron的一个字段：逗号分隔，每项为`*`、`n`、`a-b`或Jenkins的`H`、`H(a-b)`，都可带`/step`，
+    /// 如“0,30”、“*/15”、“H(0-29)”、“H/15”。H按key的哈希值选中，见[`ConfigOperator::hashed`]、
+    /// [`ConfigOperator::hashed_step`]
+    fn parse_cron(text: &str, key: &str) -> Result<Self> {
+        let number = |x: &str| {
+            let val = x
+                .trim()
+                .parse::<u64>()
+                .map_err(|_| Error::parse_text(format!("invalid {} `{}`", Self::NAME, x)))?;
+            Self::DataTy::try_from_data(val)
+        };
+        text.split(',').try_fold(Self::_default(), |ins, item| {
+            let (item, step) = match item.split_once('/') {
+                Some((item, step)) => {
+                    let step = step
+                        .trim()
+                        .parse::<u64>()
+                        .map_err(|_| Error::parse_text(format!("invalid step `{}`", step)))?;
+                    (item.trim(), Some(step))
+                }
+                None => (item.trim(), None),
+            };
+            let (hashed, range) = match item.strip_prefix('H') {
+                Some("") => (true, "*"),
+                Some(range) => match range.strip_prefix('(').and_then(|x| x.strip_suffix(')')) {
+                    Some(range) => (true, range),
+                    None => {
+                        let message = format!("invalid hashed {} `{}`", Self::NAME, item);
+                        return Err(Error::parse_text(message));
+                    }
+                },
+                None => (false, item),
+            };
+            let range = match (range, range.split_once('-')) {
+                ("*", _) => (Bound::Unbounded, Bound::Unbounded),
+                (_, Some((first, last))) => (
+                    Bound::Included(number(first)?),
+                    Bound::Included(number(last)?),
+                ),
+                // 与cron一致，“5/15”从5开始每隔15
+                (first, None) if step.is_some() && !hashed => {
+                    (Bound::Included(number(first)?), Bound::Unbounded)
+                }
+                (value, None) => (
+                    Bound::Included(number(value)?),
+                    Bound::Included(number(value)?),
+                ),
+            };
+            let selected = match (hashed, step) {
+                (true, Some(step)) => Self::hashed_step(key, range, step)?,
+                (true, None) => Self::hashed(key, range)?,
+                (false, Some(step)) => Self::default_range_step(range, step)?,
+                (false, None) => Self::default_range(range)?,
+            };
+            Ok(ins.merge(&selected))
+        })
+    }
     fn add(mut self, index: Self::DataTy) -> Self {
         let index = index.as_data();
         self._val_mut(self._val() | (1 << index));
         self
     }
-    fn add_range(mut self, range: impl RangeBounds<Self::DataTy>) -> Result<Self> {
+    fn add_range(self, range: impl RangeBounds<Self::DataTy>) -> Result<Self> {
+        self.add_range_step(range, 1)
+    }
+    /// 从最小值开始，每隔interval选中一个值，如Minuters::every(15)为0、15、30、45分。
+    /// 与[`ConfigOperator::add_range_step`]一样，interval为0时返回[`Error::InvalidStep`]
+    fn every(interval: u64) -> Result<Self> {
+        Self::default_range_step(.., interval)
+    }
+    #[inline]
+    fn default_range_step(range: impl RangeBounds<Self::DataTy>, step: u64) -> Result<Self> {
+        let ins = Self::_default();
+        ins.add_range_step(range, step)
+    }
+    /// 在区间内从起点开始，每隔step选中一个值，如(M3..=M58, 5)为3、8……58分
+    fn add_range_step(mut self, range: impl RangeBounds<Self::DataTy>, step: u64) -> Result<Self> {
+        if step == 0 {
+            return Err(Error::InvalidStep {
+                field: Self::NAME,
+                step,
+            });
+        }
         let mut first = match range.start_bound() {
             Bound::Unbounded => Self::MIN,
             Bound::Included(first) => first.as_data(),
@@ -81,15 +204,23 @@ pub trait ConfigOperator: Sized {
         let end = match range.end_bound() {
             Bound::Unbounded => Self::MAX,
             Bound::Included(end) => end.as_data(),
-            Bound::Excluded(end) => end.as_data() - 1,
+            Bound::Excluded(end) => match end.as_data() {
+                end if end > Self::MIN => end - 1,
+                _ => {
+                    return Err(Error::InvalidBound {
+                        field: Self::NAME,
+                        reason: "excluded end should be greater than the minimum",
+                    })
+                }
+            },
         };
         if first > end {
-            bail!("error:{} > {}", first, end);
+            return Err(Error::empty_range(Self::NAME, first, end));
         }
         let mut val = self._val();
         while first <= end {
             val |= 1 << first;
-            first += 1;
+            first = first.saturating_add(step);
         }
         self._val_mut(val);
         Ok(self)
@@ -108,16 +239,26 @@ pub trait ConfigOperator: Sized {
     }
 
     fn to_vec(&self) -> Vec<u64> {
-        let mut res = Vec::new();
-        let val = self._val();
-        let mut first = Self::MIN;
-        while first <= Self::MAX {
-            if (val & (1 << first)) > 0 {
-                res.push(first);
-            }
-            first += 1;
-        }
-        res
+        self.iter().map(AsBizData::as_data).collect()
+    }
+    /// 按从小到大的顺序遍历持有值
+    fn iter(&self) -> ConfigIter<Self::DataTy> {
+        ConfigIter::new(self._val() & Self::_mask())
+    }
+    /// 持有值的个数
+    fn len(&self) -> usize {
+        (self._val() & Self::_mask()).count_ones() as usize
+    }
+    fn is_empty(&self) -> bool {
+        self.is_zero()
+    }
+    /// 最小的持有值，啥都没有选时为None
+    fn first(&self) -> Option<Self::DataTy> {
+        self._min_val().map(Self::DataTy::from_data)
+    }
+    /// 最大的持有值，啥都没有选时为None
+    fn last(&self) -> Option<Self::DataTy> {
+        self._max_val().map(Self::DataTy::from_data)
     }
     fn contain(&self, index: Self::DataTy) -> bool {
         let index = index.as_data();
@@ -127,28 +268,41 @@ pub trait ConfigOperator: Sized {
     fn next(&self, index: Self::DataTy) -> Option<Self::DataTy>;
     /// 取下一个持有值，不包括index
     fn _next(&self, index: Self::DataTy) -> Option<u64> {
-        let mut first = index.as_data() + 1;
-        let val = self._val();
-        while first <= Self::MAX {
-            if (val & (1 << first)) > 0 {
-                return Some(first);
-            }
-            first += 1;
-        }
-        None
+        let above = u64::MAX
+            .checked_shl(index.as_data() as u32 + 1)
+            .unwrap_or(0);
+        lowest(self._val() & Self::_mask() & above)
     }
-    fn min_val(&self) -> Self::DataTy;
-    /// 取最小的持有值
-    fn _min_val(&self) -> u64 {
-        let mut first = Self::MIN;
-        let val = self._val();
-        while first <= Self::MAX {
-            if (val & (1 << first)) > 0 {
-                return first;
-            }
-            first += 1;
-        }
-        unreachable!("it is a bug");
+    fn prev(&self, index: Self::DataTy) -> Option<Self::DataTy> {
+        self._prev(index).map(Self::DataTy::from_data)
+    }
+    /// 取上一个持有值，不包括index
+    fn _prev(&self, index: Self::DataTy) -> Option<u64> {
+        let below = (1u64 << index.as_data()) - 1;
+        highest(self._val() & Self::_mask() & below)
+    }
+    /// 最小的持有值。啥都没有选时panic，可用[`ConfigOperator::first`]得到None
+    fn min_val(&self) -> Self::DataTy {
+        self.first()
+            .unwrap_or_else(|| panic!("no {} is selected", Self::NAME))
+    }
+    /// 取最小的持有值，啥都没有选时为None
+    fn _min_val(&self) -> Option<u64> {
+        lowest(self._val() & Self::_mask())
+    }
+    /// 最大的持有值。啥都没有选时panic，可用[`ConfigOperator::last`]得到None
+    fn max_val(&self) -> Self::DataTy {
+        self.last()
+            .unwrap_or_else(|| panic!("no {} is selected", Self::NAME))
+    }
+    /// 取最大的持有值，啥都没有选时为None
+    fn _max_val(&self) -> Option<u64> {
+        highest(self._val() & Self::_mask())
+    }
+    /// MIN..=MAX对应的位
+    #[inline]
+    fn _mask() -> u64 {
+        (u64::MAX >> (63 - Self::MAX)) & (u64::MAX << Self::MIN)
     }
     fn _val(&self) -> u64;
     fn _val_mut(&mut self, val: u64);
@@ -159,16 +313,100 @@ pub trait ConfigOperator: Sized {
     }
 }
 
+/// 配置项持有值的迭代器，见[`ConfigOperator::iter`]
+#[derive(Clone, Debug)]
+pub struct ConfigIter<T> {
+    // 尚未遍历的持有值
+    val: u64,
+    _data: PhantomData<T>,
+}
+
+impl<T> ConfigIter<T> {
+    pub(crate) fn new(val: u64) -> Self {
+        Self {
+            val,
+            _data: PhantomData,
+        }
+    }
+}
+
+/// 最低的为1的位
+#[inline]
+pub(crate) fn lowest(val: u64) -> Option<u64> {
+    match val {
+        0 => None,
+        _ => Some(val.trailing_zeros() as u64),
+    }
+}
+
+/// 最高的为1的位
+#[inline]
+pub(crate) fn highest(val: u64) -> Option<u64> {
+    match val {
+        0 => None,
+        _ => Some(63 - val.leading_zeros() as u64),
+    }
+}
+
+/// 不小于index的位
+#[inline]
+pub(crate) fn bits_from(index: u32) -> u64 {
+    u64::MAX.checked_shl(index).unwrap_or(0)
+}
+
+/// 不大于index的位
+#[inline]
+pub(crate) fn bits_to(index: u32) -> u64 {
+    u64::MAX >> (63 - index)
+}
+
+/// 小于index的位
+#[inline]
+pub(crate) fn bits_below(index: u32) -> u64 {
+    (1u64 << index) - 1
+}
+
+impl<T: FromData<u64>> Iterator for ConfigIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        let index = lowest(self.val)?;
+        self.val &= self.val - 1;
+        Some(T::from_data(index))
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.val.count_ones() as usize;
+        (len, Some(len))
+    }
+}
+
+impl<T: FromData<u64>> DoubleEndedIterator for ConfigIter<T> {
+    fn next_back(&mut self) -> Option<T> {
+        let index = highest(self.val)?;
+        self.val &= !(1 << index);
+        Some(T::from_data(index))
+    }
+}
+
+impl<T: FromData<u64>> ExactSizeIterator for ConfigIter<T> {}
+
 pub trait AsBizData<Ty>: Copy {
     fn as_data(self) -> Ty;
 }
 
-pub trait FromData<Ty> {
-    fn from_data(val: Ty) -> Self;
+pub(crate) use private::FromData;
+
+mod private {
+    /// 超出取值范围时panic，只在crate内部用于已知合法的值（如配置里为1的位），不对外导出。
+    /// 处理用户输入请用[`TryFromData`](super::TryFromData)或`TryFrom`
+    pub trait FromData<Ty> {
+        fn from_data(val: Ty) -> Self;
+    }
 }
 
-pub trait TryFromData<Ty>: FromData<Ty> {
-    fn try_from_data(val: Ty) -> anyhow::Result<Self>
+/// 按整数值构造，超出取值范围时返回[`Error::OutOfRange`]
+pub trait TryFromData<Ty> {
+    fn try_from_data(val: Ty) -> Result<Self>
     where
         Self: Sized;
 }
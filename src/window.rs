@@ -0,0 +1,148 @@
+use crate::schedule::Schedule;
+use chrono::{Duration, NaiveDateTime};
+use std::ops::{Add, Sub};
+
+/// 有效期：只在[not_before, not_after]时间范围内触发，未设置的一端不限制
+#[derive(Debug, Clone)]
+pub struct Window<S: Schedule> {
+    schedule: S,
+    not_before: Option<NaiveDateTime>,
+    not_after: Option<NaiveDateTime>,
+}
+
+impl<S: Schedule> Window<S> {
+    pub fn new(schedule: S) -> Self {
+        Self {
+            schedule,
+            not_before: None,
+            not_after: None,
+        }
+    }
+    /// 最早的触发时间点（包含）
+    pub fn not_before(mut self, not_before: NaiveDateTime) -> Self {
+        self.not_before = Some(not_before);
+        self
+    }
+    /// 最晚的触发时间点（包含）
+    pub fn not_after(mut self, not_after: NaiveDateTime) -> Self {
+        self.not_after = Some(not_after);
+        self
+    }
+    fn contain(&self, datetime: NaiveDateTime) -> bool {
+        self.not_before.map_or(true, |x| x <= datetime)
+            && self.not_after.map_or(true, |x| datetime <= x)
+    }
+}
+
+impl<S: Schedule> Schedule for Window<S> {
+    fn next_with_time(&self, mut now: NaiveDateTime) -> Option<NaiveDateTime> {
+        if let Some(not_before) = self.not_before {
+            now = now.max(not_before.sub(Duration::seconds(1)));
+        }
+        if self.not_after.is_some_and(|x| now >= x) {
+            return None;
+        }
+        self.schedule
+            .next_with_time(now)
+            .filter(|next| self.contain(*next))
+    }
+    fn prev_with_time(&self, mut now: NaiveDateTime) -> Option<NaiveDateTime> {
+        if let Some(not_after) = self.not_after {
+            now = now.min(not_after.add(Duration::seconds(1)));
+        }
+        if self.not_before.is_some_and(|x| now <= x) {
+            return None;
+        }
+        self.schedule
+            .prev_with_time(now)
+            .filter(|prev| self.contain(*prev))
+    }
+    fn matches(&self, datetime: NaiveDateTime) -> bool {
+        self.contain(datetime) && self.schedule.matches(datetime)
+    }
+    fn next_unmatched(&self, now: NaiveDateTime) -> Option<NaiveDateTime> {
+        let start = now.checked_add_signed(Duration::seconds(1))?;
+        if !self.contain(start) {
+            return Some(start);
+        }
+        // 有效期之后都不符合
+        match (self.schedule.next_unmatched(now), self.not_after) {
+            (Some(gap), Some(not_after)) if gap > not_after => {
+                not_after.checked_add_signed(Duration::seconds(1))
+            }
+            (None, Some(not_after)) => not_after.checked_add_signed(Duration::seconds(1)),
+            (gap, _) => gap,
+        }
+    }
+    fn prev_unmatched(&self, now: NaiveDateTime) -> Option<NaiveDateTime> {
+        let start = now.checked_sub_signed(Duration::seconds(1))?;
+        if !self.contain(start) {
+            return Some(start);
+        }
+        // 有效期之前都不符合
+        match (self.schedule.prev_unmatched(now), self.not_before) {
+            (Some(gap), Some(not_before)) if gap < not_before => {
+                not_before.checked_sub_signed(Duration::seconds(1))
+            }
+            (None, Some(not_before)) => not_before.checked_sub_signed(Duration::seconds(1)),
+            (gap, _) => gap,
+        }
+    }
+    fn valid_range(&self) -> (Option<NaiveDateTime>, Option<NaiveDateTime>) {
+        let (start, end) = self.schedule.valid_range();
+        (
+            start.max(self.not_before),
+            match (end, self.not_after) {
+                (Some(end), Some(not_after)) => Some(end.min(not_after)),
+                (end, not_after) => end.or(not_after),
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::conf::test::datetime;
+    use crate::*;
+
+    #[test]
+    fn test_window() -> anyhow::Result<()> {
+        let conf = configure_weekday(WeekDays::default_value(W1))
+            .build_with_hours(Hours::default_value(H10))
+            .build_with_minuter(Minuters::default_value(M0))
+            .build_with_second(Seconds::default_value(S0));
+        let campaign = conf
+            .clone()
+            .not_before(datetime(2022, 8, 8, 10, 0, 0))
+            .not_after(datetime(2022, 8, 29, 9, 0, 0));
+        assert_eq!(
+            campaign.next_with_time(datetime(2022, 1, 1, 0, 0, 0)),
+            Some(datetime(2022, 8, 8, 10, 0, 0))
+        );
+        assert_eq!(campaign.next_with_time(datetime(2022, 8, 22, 10, 0, 0)), None);
+        assert_eq!(
+            campaign.prev_with_time(datetime(2023, 1, 1, 0, 0, 0)),
+            Some(datetime(2022, 8, 22, 10, 0, 0))
+        );
+        assert_eq!(campaign.prev_with_time(datetime(2022, 8, 8, 10, 0, 0)), None);
+        assert!(!campaign.matches(datetime(2022, 8, 1, 10, 0, 0)));
+        // 开放区间按有效期截断
+        assert_eq!(
+            campaign.datetimes(..)?,
+            vec![
+                datetime(2022, 8, 8, 10, 0, 0),
+                datetime(2022, 8, 15, 10, 0, 0),
+                datetime(2022, 8, 22, 10, 0, 0),
+            ]
+        );
+        assert_eq!(
+            campaign.datetimes(datetime(2022, 8, 10, 0, 0, 0)..)?,
+            vec![datetime(2022, 8, 15, 10, 0, 0), datetime(2022, 8, 22, 10, 0, 0)]
+        );
+        assert!(Window::new(conf)
+            .not_before(datetime(2022, 8, 8, 10, 0, 0))
+            .datetimes(datetime(2022, 8, 10, 0, 0, 0)..)
+            .is_err());
+        Ok(())
+    }
+}